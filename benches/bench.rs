@@ -95,7 +95,34 @@ fn compare_routers(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, compare_routers);
+fn capture_params(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Capture Params");
+
+    let mut matchit = matchit::Router::new();
+    for route in register!(brackets) {
+        matchit.insert(route, true).unwrap();
+    }
+
+    group.bench_function("at", |b| {
+        b.iter(|| {
+            for route in black_box(call()) {
+                black_box(matchit.at(route).unwrap());
+            }
+        });
+    });
+
+    group.bench_function("value_at", |b| {
+        b.iter(|| {
+            for route in black_box(call()) {
+                black_box(matchit.value_at(route).unwrap());
+            }
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, compare_routers, capture_params);
 criterion_main!(benches);
 
 macro_rules! register {
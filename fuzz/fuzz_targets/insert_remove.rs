@@ -0,0 +1,28 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::collections::HashSet;
+
+// Applies a sequence of insertions (`true`) and removals (`false`), checking that no
+// operation panics and that the tree stays consistent after every operation.
+fuzz_target!(|ops: Vec<(bool, String)>| {
+    let mut router = matchit::Router::new();
+    let mut routes = HashSet::new();
+
+    for (insert, route) in ops {
+        if insert {
+            if router.insert(route.as_str(), route.clone()).is_ok() {
+                assert!(routes.insert(route));
+            }
+        } else {
+            let removed = router.remove(route.as_str());
+            assert_eq!(removed.is_some(), routes.remove(&route), "{route:?}");
+        }
+
+        if let Err(err) = router.check_invariants() {
+            panic!("{err}: {:?}", router.root);
+        }
+
+        assert_eq!(router.len(), routes.len());
+    }
+});
@@ -0,0 +1,166 @@
+#ifndef MATCHIT_H
+#define MATCHIT_H
+
+/* Generated with cbindgen, do not edit by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * The operation succeeded.
+ */
+#define MATCHIT_OK 0
+
+/**
+ * The route conflicts with an existing route, see [`InsertError::Conflict`].
+ */
+#define MATCHIT_ERR_CONFLICT 1
+
+/**
+ * See [`InsertError::InvalidParamSegment`].
+ */
+#define MATCHIT_ERR_INVALID_PARAM_SEGMENT 2
+
+/**
+ * See [`InsertError::InvalidParam`].
+ */
+#define MATCHIT_ERR_INVALID_PARAM 3
+
+/**
+ * See [`InsertError::InvalidCatchAll`].
+ */
+#define MATCHIT_ERR_INVALID_CATCH_ALL 4
+
+/**
+ * No route matched the path, see [`MatchError::NotFound`](crate::MatchError::NotFound).
+ */
+#define MATCHIT_ERR_NOT_FOUND 5
+
+/**
+ * The route is not valid UTF-8.
+ */
+#define MATCHIT_ERR_INVALID_UTF8 6
+
+/**
+ * A required pointer was null.
+ */
+#define MATCHIT_ERR_NULL 7
+
+/**
+ * The parameter buffer is too small to hold the route parameters.
+ */
+#define MATCHIT_ERR_BUFFER_TOO_SMALL 8
+
+/**
+ * See [`InsertError::IllegalCharacter`].
+ */
+#define MATCHIT_ERR_ILLEGAL_CHARACTER 9
+
+/**
+ * See [`InsertError::TemplateTooLong`].
+ */
+#define MATCHIT_ERR_TEMPLATE_TOO_LONG 10
+
+/**
+ * See [`InsertError::TooManyParams`].
+ */
+#define MATCHIT_ERR_TOO_MANY_PARAMS 11
+
+/**
+ * The operation panicked, which is a bug in the router.
+ */
+#define MATCHIT_ERR_PANIC 12
+
+/**
+ * An opaque router, created with [`matchit_router_new`].
+ */
+typedef struct MatchitRouter MatchitRouter;
+
+/**
+ * A route parameter captured by [`matchit_at`].
+ */
+typedef struct MatchitParam {
+  /**
+   * The name of the parameter, which is valid until the router is modified or freed.
+   *
+   * The name is not null-terminated.
+   */
+  const uint8_t *key;
+  /**
+   * The length of the name in bytes.
+   */
+  size_t key_len;
+  /**
+   * The offset of the parameter value in the matched path.
+   */
+  size_t offset;
+  /**
+   * The length of the parameter value in bytes.
+   */
+  size_t len;
+} MatchitParam;
+
+/**
+ * Creates a new, empty router.
+ *
+ * The router must be freed with [`matchit_router_free`].
+ */
+MatchitRouter *matchit_router_new(void);
+
+/**
+ * Frees a router created with [`matchit_router_new`].
+ *
+ * # Safety
+ *
+ * `router` must be null or a pointer returned by [`matchit_router_new`] that has not
+ * already been freed, and must not be used by any other thread.
+ */
+void matchit_router_free(MatchitRouter *router);
+
+/**
+ * Enables or disables strict validation of inserted routes, see
+ * [`Router::set_strict`].
+ *
+ * # Safety
+ *
+ * `router` must be a live router that is not used by any other thread.
+ */
+int32_t matchit_router_set_strict(MatchitRouter *router, bool strict);
+
+/**
+ * Inserts a route into the router, with the given value.
+ *
+ * # Safety
+ *
+ * `router` must be a live router that is not used by any other thread, and `route` must
+ * point to `route_len` readable bytes.
+ */
+int32_t matchit_insert(MatchitRouter *router,
+                       const uint8_t *route,
+                       size_t route_len,
+                       uint64_t value);
+
+/**
+ * Matches the given path, writing the matched value to `value` and the route parameters
+ * to `params`.
+ *
+ * `params_len` must hold the capacity of `params`, and is set to the number of route
+ * parameters on success or [`MATCHIT_ERR_BUFFER_TOO_SMALL`]. `params` may be null if the
+ * capacity is zero. The path does not need to be valid UTF-8, and is matched like
+ * [`Router::at_bytes`].
+ *
+ * # Safety
+ *
+ * `router` must be a live router that is not being modified by any other thread, `path`
+ * must point to `path_len` readable bytes, `value` and `params_len` must be writable, and
+ * `params` must point to `*params_len` writable parameters.
+ */
+int32_t matchit_at(const MatchitRouter *router,
+                   const uint8_t *path,
+                   size_t path_len,
+                   uint64_t *value,
+                   MatchitParam *params,
+                   size_t *params_len);
+
+#endif /* MATCHIT_H */
@@ -0,0 +1,281 @@
+//! The matching behavior of a router, as a table of executable cases.
+//!
+//! Each [`Case`] inserts a set of routes and matches a single path, grouped by the rule
+//! it demonstrates. The cases are run against [`Router`] by the crate's own tests, and
+//! can be run against any wrapper around a router with [`run`], to check that it does not
+//! change the matching behavior.
+//!
+//! ```
+//! use matchit::conformance::{self, Matcher};
+//! use matchit::Router;
+//!
+//! // A wrapper that stores the route alongside its value.
+//! #[derive(Default)]
+//! struct Wrapper(Router<(String, ())>);
+//!
+//! impl Matcher for Wrapper {
+//!     fn insert(&mut self, route: &str) {
+//!         self.0.insert(route, (route.to_owned(), ())).unwrap();
+//!     }
+//!
+//!     fn at(&self, path: &str) -> Option<(String, Vec<(String, String)>)> {
+//!         let matched = self.0.at(path).ok()?;
+//!         let params = matched.params.iter().map(|param| {
+//!             let key = String::from_utf8(param.key.to_vec()).unwrap();
+//!             let value = String::from_utf8(param.value.to_vec()).unwrap();
+//!             (key, value)
+//!         });
+//!
+//!         Some((matched.value.0.clone(), params.collect()))
+//!     }
+//! }
+//!
+//! conformance::run(Wrapper::default).unwrap();
+//! ```
+
+use crate::Router;
+
+use std::fmt;
+
+/// A rule describing part of the matching behavior.
+pub mod rule {
+    /// Static segments are preferred over route parameters.
+    pub const STATIC_OVER_PARAM: &str = "static routes take precedence over parameters";
+    /// Static segments are preferred over catch-all parameters.
+    pub const STATIC_OVER_CATCH_ALL: &str = "static routes take precedence over catch-alls";
+    /// Trailing slashes are significant.
+    pub const TRAILING_SLASH: &str = "trailing slashes are significant";
+    /// Parameters and catch-alls do not match an empty path suffix, but parameters can
+    /// match an empty segment in the middle of a path.
+    pub const EMPTY_SEGMENT: &str = "parameters only match empty segments before a slash";
+    /// A failed match of a more specific route falls back to less specific routes.
+    pub const BACKTRACKING: &str = "failed matches backtrack to less specific routes";
+    /// Parameters are returned in the order they appear in the route.
+    pub const PARAM_ORDER: &str = "parameters are returned in route order";
+    /// Paths are matched as-is, without decoding or normalization.
+    pub const RAW_PATH: &str = "paths are matched without decoding";
+}
+
+/// A single case of the matching behavior.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Case {
+    /// The rule this case demonstrates, one of the constants in [`rule`].
+    pub rule: &'static str,
+    /// The routes inserted into the router.
+    pub routes: &'static [&'static str],
+    /// The path that is matched.
+    pub path: &'static str,
+    /// The expected matched route and parameters, or `None` if the path must not match.
+    pub expected: Option<(&'static str, &'static [(&'static str, &'static str)])>,
+}
+
+/// The table of cases, grouped by rule.
+///
+/// New matching behavior should be covered by adding cases to this table.
+pub const CASES: &[Case] = &[
+    Case {
+        rule: rule::STATIC_OVER_PARAM,
+        routes: &["/users/{id}", "/users/new"],
+        path: "/users/new",
+        expected: Some(("/users/new", &[])),
+    },
+    Case {
+        rule: rule::STATIC_OVER_PARAM,
+        routes: &["/users/{id}", "/users/new"],
+        path: "/users/newer",
+        expected: Some(("/users/{id}", &[("id", "newer")])),
+    },
+    Case {
+        rule: rule::STATIC_OVER_PARAM,
+        routes: &["/{resource}/list", "/users/list"],
+        path: "/users/list",
+        expected: Some(("/users/list", &[])),
+    },
+    Case {
+        rule: rule::STATIC_OVER_CATCH_ALL,
+        routes: &["/{*path}", "/index.html"],
+        path: "/index.html",
+        expected: Some(("/index.html", &[])),
+    },
+    Case {
+        rule: rule::STATIC_OVER_CATCH_ALL,
+        routes: &["/{*path}", "/index.html"],
+        path: "/index.htm",
+        expected: Some(("/{*path}", &[("path", "index.htm")])),
+    },
+    Case {
+        rule: rule::STATIC_OVER_CATCH_ALL,
+        routes: &["/{*path}", "/files/{name}"],
+        path: "/files/a.txt",
+        expected: Some(("/files/{name}", &[("name", "a.txt")])),
+    },
+    Case {
+        rule: rule::STATIC_OVER_CATCH_ALL,
+        routes: &["/{*path}", "/files/{name}"],
+        path: "/files/a/b.txt",
+        expected: Some(("/{*path}", &[("path", "files/a/b.txt")])),
+    },
+    Case {
+        rule: rule::TRAILING_SLASH,
+        routes: &["/users"],
+        path: "/users/",
+        expected: None,
+    },
+    Case {
+        rule: rule::TRAILING_SLASH,
+        routes: &["/users/"],
+        path: "/users",
+        expected: None,
+    },
+    Case {
+        rule: rule::TRAILING_SLASH,
+        routes: &["/users/{id}"],
+        path: "/users/1/",
+        expected: None,
+    },
+    Case {
+        rule: rule::TRAILING_SLASH,
+        routes: &["/static/{*path}"],
+        path: "/static/a/",
+        expected: Some(("/static/{*path}", &[("path", "a/")])),
+    },
+    Case {
+        rule: rule::EMPTY_SEGMENT,
+        routes: &["/users/{id}"],
+        path: "/users/",
+        expected: None,
+    },
+    Case {
+        rule: rule::EMPTY_SEGMENT,
+        routes: &["/users/{id}/posts"],
+        path: "/users//posts",
+        expected: Some(("/users/{id}/posts", &[("id", "")])),
+    },
+    Case {
+        rule: rule::EMPTY_SEGMENT,
+        routes: &["/static/{*path}"],
+        path: "/static/",
+        expected: None,
+    },
+    Case {
+        rule: rule::BACKTRACKING,
+        routes: &["/{object}/{id}", "/secret/{id}/path"],
+        path: "/secret/978",
+        expected: Some(("/{object}/{id}", &[("object", "secret"), ("id", "978")])),
+    },
+    Case {
+        rule: rule::BACKTRACKING,
+        routes: &["/api/users/{id}", "/{*rest}"],
+        path: "/api/users/1/posts",
+        expected: Some(("/{*rest}", &[("rest", "api/users/1/posts")])),
+    },
+    Case {
+        rule: rule::BACKTRACKING,
+        routes: &["/api/users/{id}", "/api/{*rest}"],
+        path: "/api/users",
+        expected: Some(("/api/{*rest}", &[("rest", "users")])),
+    },
+    Case {
+        rule: rule::PARAM_ORDER,
+        routes: &["/users/{user}/posts/{post}/{*rest}"],
+        path: "/users/1/posts/2/a/b",
+        expected: Some((
+            "/users/{user}/posts/{post}/{*rest}",
+            &[("user", "1"), ("post", "2"), ("rest", "a/b")],
+        )),
+    },
+    Case {
+        rule: rule::PARAM_ORDER,
+        routes: &["/{b}/{a}"],
+        path: "/1/2",
+        expected: Some(("/{b}/{a}", &[("b", "1"), ("a", "2")])),
+    },
+    Case {
+        rule: rule::RAW_PATH,
+        routes: &["/files/{name}"],
+        path: "/files/a%2Fb",
+        expected: Some(("/files/{name}", &[("name", "a%2Fb")])),
+    },
+    Case {
+        rule: rule::RAW_PATH,
+        routes: &["/files/{name}"],
+        path: "/files/a/../b",
+        expected: None,
+    },
+    Case {
+        rule: rule::RAW_PATH,
+        routes: &["/Users"],
+        path: "/users",
+        expected: None,
+    },
+];
+
+/// A router, or a wrapper around one, that can be checked against the [`CASES`].
+pub trait Matcher {
+    /// Inserts the given route.
+    fn insert(&mut self, route: &str);
+
+    /// Matches the given path, returning the matched route and its parameters.
+    fn at(&self, path: &str) -> Option<(String, Vec<(String, String)>)>;
+}
+
+impl Matcher for Router<String> {
+    fn insert(&mut self, route: &str) {
+        Router::insert(self, route, route.to_owned()).unwrap();
+    }
+
+    fn at(&self, path: &str) -> Option<(String, Vec<(String, String)>)> {
+        let matched = Router::at(self, path).ok()?;
+        let params = matched.params.iter().map(|param| {
+            let key = String::from_utf8_lossy(param.key).into_owned();
+            let value = String::from_utf8_lossy(param.value).into_owned();
+            (key, value)
+        });
+
+        Some((matched.value.clone(), params.collect()))
+    }
+}
+
+/// A case that did not match as expected, returned by [`run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// The case that failed.
+    pub case: &'static Case,
+    /// The actual matched route and parameters.
+    pub actual: Option<(String, Vec<(String, String)>)>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: matching {:?} against {:?}, expected {:?} but found {:?}",
+            self.case.rule, self.case.path, self.case.routes, self.case.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Runs every case against a fresh matcher created by `new`, returning the first case
+/// that did not match as expected.
+pub fn run<M: Matcher>(mut new: impl FnMut() -> M) -> Result<(), Failure> {
+    for case in CASES {
+        let mut matcher = new();
+        for route in case.routes {
+            matcher.insert(route);
+        }
+
+        let actual = matcher.at(case.path);
+        let expected = case.expected.map(|(route, params)| {
+            let params = params.iter().map(|&(k, v)| (k.to_owned(), v.to_owned()));
+            (route.to_owned(), params.collect::<Vec<_>>())
+        });
+
+        if actual != expected {
+            return Err(Failure { case, actual });
+        }
+    }
+
+    Ok(())
+}
@@ -20,6 +20,56 @@ pub enum InsertError {
     InvalidParam,
     /// Catch-all parameters are only allowed at the end of a path.
     InvalidCatchAll,
+    /// The route contains a character that is not allowed in a URI path, or a malformed
+    /// percent-encoded sequence.
+    ///
+    /// This is only returned by routers in strict mode, see [`Router::set_strict`].
+    ///
+    /// [`Router::set_strict`]: crate::Router::set_strict
+    IllegalCharacter {
+        /// The illegal character.
+        ch: char,
+        /// The byte offset of the character in the route.
+        at: usize,
+    },
+    /// Attempted to insert a route for a range of versions that overlaps with a range
+    /// already registered for the same route, see [`Router::insert_version_range`].
+    ///
+    /// [`Router::insert_version_range`]: crate::Router::insert_version_range
+    VersionConflict {
+        /// The route, without the version prefix.
+        route: String,
+        /// The first and last version of the existing range.
+        existing: (u32, u32),
+        /// The first and last version of the range that was being inserted.
+        inserted: (u32, u32),
+    },
+    /// The route declares a parameter whose name is reserved, such as `version` for
+    /// routes inserted with [`Router::insert_version_range`].
+    ///
+    /// [`Router::insert_version_range`]: crate::Router::insert_version_range
+    ReservedParam {
+        /// The name of the parameter.
+        name: String,
+    },
+    /// The route is longer than the maximum length, see [`Router::set_max_template_len`].
+    ///
+    /// [`Router::set_max_template_len`]: crate::Router::set_max_template_len
+    TemplateTooLong {
+        /// The length of the route in bytes.
+        len: usize,
+        /// The maximum length.
+        max: usize,
+    },
+    /// The route has more parameters than allowed, see [`Router::set_max_params`].
+    ///
+    /// [`Router::set_max_params`]: crate::Router::set_max_params
+    TooManyParams {
+        /// The number of parameters in the route, including any catch-all parameter.
+        count: usize,
+        /// The maximum number of parameters.
+        max: usize,
+    },
 }
 
 impl fmt::Display for InsertError {
@@ -40,6 +90,33 @@ impl fmt::Display for InsertError {
                 f,
                 "Catch-all parameters are only allowed at the end of a route"
             ),
+            Self::IllegalCharacter { ch, at } => write!(
+                f,
+                "Character {:?} at index {} is not allowed in a URI path",
+                ch, at
+            ),
+            Self::VersionConflict {
+                route,
+                existing,
+                inserted,
+            } => write!(
+                f,
+                "Versions {}..={} of route {} overlap with previously registered versions {}..={}",
+                inserted.0, inserted.1, route, existing.0, existing.1
+            ),
+            Self::ReservedParam { name } => {
+                write!(f, "Parameter name {:?} is reserved", name)
+            }
+            Self::TemplateTooLong { len, max } => write!(
+                f,
+                "Route of {} bytes exceeds the maximum length of {} bytes",
+                len, max
+            ),
+            Self::TooManyParams { count, max } => write!(
+                f,
+                "Route with {} parameters exceeds the maximum of {} parameters",
+                count, max
+            ),
         }
     }
 }
@@ -90,6 +167,240 @@ impl InsertError {
     }
 }
 
+/// Represents errors that can occur when inserting a route with
+/// [`Router::insert_checked`](crate::Router::insert_checked).
+///
+/// Unlike [`InsertError`], syntax errors are reported with the byte offset in the route
+/// where they occur.
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CheckedInsertError {
+    /// The route is empty.
+    EmptyRoute,
+    /// A parameter has an empty name, such as `{}` or `{*}`.
+    EmptyParameterName {
+        /// The byte offset of the opening brace.
+        pos: usize,
+    },
+    /// A parameter name contains `*`, `/`, or `{`.
+    InvalidParameterName {
+        /// The byte offset of the opening brace.
+        pos: usize,
+    },
+    /// An opening brace has no closing brace.
+    UnclosedBrace {
+        /// The byte offset of the opening brace.
+        pos: usize,
+    },
+    /// A closing brace has no opening brace.
+    UnmatchedBrace {
+        /// The byte offset of the closing brace.
+        pos: usize,
+    },
+    /// A parameter is followed by something other than `/` in the same segment.
+    SuffixAfterParameter {
+        /// The byte offset of the first character after the parameter.
+        pos: usize,
+    },
+    /// A catch-all parameter is not at the end of the route.
+    WildcardNotAtEnd {
+        /// The byte offset of the opening brace.
+        pos: usize,
+    },
+    /// Two parameters in the route have the same name.
+    DuplicateParameterName {
+        /// The name of the parameters.
+        name: String,
+        /// The byte offset of the opening brace of the first parameter.
+        first_pos: usize,
+        /// The byte offset of the opening brace of the second parameter.
+        second_pos: usize,
+    },
+    /// The route contains a character that is not allowed in a URI path, see
+    /// [`InsertError::IllegalCharacter`].
+    IllegalCharacter {
+        /// The illegal character.
+        ch: char,
+        /// The byte offset of the character.
+        pos: usize,
+    },
+    /// The route is longer than the maximum length, see [`InsertError::TemplateTooLong`].
+    TemplateTooLong {
+        /// The length of the route in bytes.
+        len: usize,
+        /// The maximum length.
+        max: usize,
+    },
+    /// The route has more parameters than allowed, see [`InsertError::TooManyParams`].
+    TooManyParams {
+        /// The number of parameters in the route, including any catch-all parameter.
+        count: usize,
+        /// The maximum number of parameters.
+        max: usize,
+    },
+    /// The route conflicts with an existing route.
+    Conflict {
+        /// The existing route.
+        existing: String,
+    },
+    /// Any other error returned by [`Router::insert`](crate::Router::insert).
+    Other(InsertError),
+}
+
+impl From<InsertError> for CheckedInsertError {
+    fn from(err: InsertError) -> Self {
+        match err {
+            InsertError::Conflict { with } => Self::Conflict { existing: with },
+            InsertError::IllegalCharacter { ch, at } => Self::IllegalCharacter { ch, pos: at },
+            InsertError::TemplateTooLong { len, max } => Self::TemplateTooLong { len, max },
+            InsertError::TooManyParams { count, max } => Self::TooManyParams { count, max },
+            err => Self::Other(err),
+        }
+    }
+}
+
+impl fmt::Display for CheckedInsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyRoute => write!(f, "Route is empty"),
+            Self::EmptyParameterName { pos } => {
+                write!(f, "Parameter at index {} has an empty name", pos)
+            }
+            Self::InvalidParameterName { pos } => {
+                write!(f, "Parameter at index {} has an invalid name", pos)
+            }
+            Self::UnclosedBrace { pos } => write!(f, "Brace at index {} is not closed", pos),
+            Self::UnmatchedBrace { pos } => {
+                write!(f, "Closing brace at index {} has no opening brace", pos)
+            }
+            Self::SuffixAfterParameter { pos } => write!(
+                f,
+                "Only `/` is allowed after a parameter, found a suffix at index {}",
+                pos
+            ),
+            Self::WildcardNotAtEnd { pos } => write!(
+                f,
+                "Catch-all parameter at index {} is not at the end of the route",
+                pos
+            ),
+            Self::DuplicateParameterName {
+                name,
+                first_pos,
+                second_pos,
+            } => write!(
+                f,
+                "Parameter {} at index {} has the same name as the parameter at index {}",
+                name, second_pos, first_pos
+            ),
+            Self::IllegalCharacter { ch, pos } => {
+                InsertError::IllegalCharacter { ch: *ch, at: *pos }.fmt(f)
+            }
+            Self::TemplateTooLong { len, max } => InsertError::TemplateTooLong {
+                len: *len,
+                max: *max,
+            }
+            .fmt(f),
+            Self::TooManyParams { count, max } => InsertError::TooManyParams {
+                count: *count,
+                max: *max,
+            }
+            .fmt(f),
+            Self::Conflict { existing } => InsertError::Conflict {
+                with: existing.clone(),
+            }
+            .fmt(f),
+            Self::Other(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for CheckedInsertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Other(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Represents errors that can occur when stripping a prefix from all routes, see
+/// [`Router::strip_prefix_from_all`](crate::Router::strip_prefix_from_all).
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StripError {
+    /// A route does not start with the prefix.
+    NotUniversal {
+        /// The route that does not start with the prefix.
+        route: String,
+    },
+    /// A route would no longer start with a `/` after stripping the prefix.
+    MissingLeadingSlash {
+        /// The route that would no longer start with a `/`.
+        route: String,
+    },
+    /// A route could not be inserted after stripping the prefix.
+    Insert(InsertError),
+}
+
+impl fmt::Display for StripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotUniversal { route } => {
+                write!(f, "Route does not start with the prefix: {}", route)
+            }
+            Self::MissingLeadingSlash { route } => {
+                write!(
+                    f,
+                    "Route does not start with a `/` once stripped: {}",
+                    route
+                )
+            }
+            Self::Insert(err) => write!(f, "Failed to insert stripped route: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for StripError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotUniversal { .. } | Self::MissingLeadingSlash { .. } => None,
+            Self::Insert(err) => Some(err),
+        }
+    }
+}
+
+/// Represents errors that can occur when building a router from a configuration, see
+/// [`Router::build_from_config`](crate::Router::build_from_config).
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ConfigError {
+    /// A route from the configuration could not be inserted.
+    Insert {
+        /// The route that could not be inserted.
+        route: String,
+        /// The reason the route could not be inserted.
+        error: InsertError,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insert { route, error } => {
+                write!(f, "Failed to insert configured route {}: {}", route, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Insert { error, .. } => Some(error),
+        }
+    }
+}
+
 /// A failed match attempt.
 ///
 /// ```
@@ -105,16 +416,102 @@ impl InsertError {
 /// }
 /// # Ok(())
 /// # }
+#[non_exhaustive]
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum MatchError {
     /// No matching route was found.
     NotFound,
+    /// No matching route was found, but one would match with the trailing slash removed.
+    ///
+    /// This is only returned by routers using [`TrailingSlashBehavior::Redirect`].
+    ///
+    /// [`TrailingSlashBehavior::Redirect`]: crate::TrailingSlashBehavior::Redirect
+    ExtraTrailingSlash,
+    /// No matching route was found, but one would match with a trailing slash added.
+    ///
+    /// This is only returned by routers using [`TrailingSlashBehavior::Redirect`].
+    ///
+    /// [`TrailingSlashBehavior::Redirect`]: crate::TrailingSlashBehavior::Redirect
+    MissingTrailingSlash,
 }
 
 impl fmt::Display for MatchError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Matching route not found")
+        match self {
+            Self::NotFound => write!(f, "Matching route not found"),
+            Self::ExtraTrailingSlash => {
+                write!(f, "Matching route found with the trailing slash removed")
+            }
+            Self::MissingTrailingSlash => {
+                write!(f, "Matching route found with a trailing slash added")
+            }
+        }
     }
 }
 
 impl std::error::Error for MatchError {}
+
+impl MatchError {
+    /// Returns the path to redirect to for a [`MatchError::ExtraTrailingSlash`] or
+    /// [`MatchError::MissingTrailingSlash`] error, given the path that was matched.
+    ///
+    /// Returns `None` for any other error.
+    ///
+    /// ```
+    /// use matchit::{Router, TrailingSlashBehavior};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new().with_trailing_slash_behavior(TrailingSlashBehavior::Redirect);
+    /// router.insert("/users", "Users")?;
+    /// router.insert("/docs/", "Docs")?;
+    ///
+    /// let err = router.at("/users/").unwrap_err();
+    /// assert_eq!(err.redirect_target("/users/").as_deref(), Some("/users"));
+    ///
+    /// let err = router.at("/docs").unwrap_err();
+    /// assert_eq!(err.redirect_target("/docs").as_deref(), Some("/docs/"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redirect_target(&self, path: &str) -> Option<String> {
+        match self {
+            Self::ExtraTrailingSlash => path.strip_suffix('/').map(str::to_owned),
+            Self::MissingTrailingSlash => Some(format!("{}/", path)),
+            _ => None,
+        }
+    }
+}
+
+/// A failed time-bounded match attempt, see [`Router::at_timeout`].
+///
+/// [`Router::at_timeout`]: crate::Router::at_timeout
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TimeoutOrMatchError {
+    /// The search took longer than the time budget.
+    Timeout,
+    /// The search completed without finding a matching route.
+    Match(MatchError),
+}
+
+impl From<MatchError> for TimeoutOrMatchError {
+    fn from(err: MatchError) -> Self {
+        Self::Match(err)
+    }
+}
+
+impl fmt::Display for TimeoutOrMatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "Matching exceeded the time budget"),
+            Self::Match(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TimeoutOrMatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Timeout => None,
+            Self::Match(err) => Some(err),
+        }
+    }
+}
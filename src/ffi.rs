@@ -0,0 +1,218 @@
+//! A C interface to the router.
+//!
+//! Values are opaque `u64` identifiers supplied by the caller, and route parameters are
+//! returned as offsets into the matched path. The generated header is in
+//! `include/matchit.h`, see `cbindgen.toml`.
+//!
+//! # Thread Safety
+//!
+//! A router can be matched against from multiple threads at once, but inserting into or
+//! freeing a router requires exclusive access, like the equivalent Rust methods.
+//!
+//! # Errors
+//!
+//! Every function that can fail returns one of the `MATCHIT_*` status codes.
+
+use crate::{InsertError, Router};
+
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice, str};
+
+/// The operation succeeded.
+pub const MATCHIT_OK: i32 = 0;
+/// The route conflicts with an existing route, see [`InsertError::Conflict`].
+pub const MATCHIT_ERR_CONFLICT: i32 = 1;
+/// See [`InsertError::InvalidParamSegment`].
+pub const MATCHIT_ERR_INVALID_PARAM_SEGMENT: i32 = 2;
+/// See [`InsertError::InvalidParam`].
+pub const MATCHIT_ERR_INVALID_PARAM: i32 = 3;
+/// See [`InsertError::InvalidCatchAll`].
+pub const MATCHIT_ERR_INVALID_CATCH_ALL: i32 = 4;
+/// No route matched the path, see [`MatchError::NotFound`](crate::MatchError::NotFound).
+pub const MATCHIT_ERR_NOT_FOUND: i32 = 5;
+/// The route is not valid UTF-8.
+pub const MATCHIT_ERR_INVALID_UTF8: i32 = 6;
+/// A required pointer was null.
+pub const MATCHIT_ERR_NULL: i32 = 7;
+/// The parameter buffer is too small to hold the route parameters.
+pub const MATCHIT_ERR_BUFFER_TOO_SMALL: i32 = 8;
+/// See [`InsertError::IllegalCharacter`].
+pub const MATCHIT_ERR_ILLEGAL_CHARACTER: i32 = 9;
+/// See [`InsertError::TemplateTooLong`].
+pub const MATCHIT_ERR_TEMPLATE_TOO_LONG: i32 = 10;
+/// See [`InsertError::TooManyParams`].
+pub const MATCHIT_ERR_TOO_MANY_PARAMS: i32 = 11;
+/// The operation panicked, which is a bug in the router.
+pub const MATCHIT_ERR_PANIC: i32 = 12;
+
+/// An opaque router, created with [`matchit_router_new`].
+pub struct MatchitRouter(Router<u64>);
+
+/// A route parameter captured by [`matchit_at`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchitParam {
+    /// The name of the parameter, which is valid until the router is modified or freed.
+    ///
+    /// The name is not null-terminated.
+    pub key: *const u8,
+    /// The length of the name in bytes.
+    pub key_len: usize,
+    /// The offset of the parameter value in the matched path.
+    pub offset: usize,
+    /// The length of the parameter value in bytes.
+    pub len: usize,
+}
+
+impl Default for MatchitRouter {
+    fn default() -> Self {
+        Self(Router::new())
+    }
+}
+
+/// Creates a new, empty router.
+///
+/// The router must be freed with [`matchit_router_free`].
+#[no_mangle]
+pub extern "C" fn matchit_router_new() -> *mut MatchitRouter {
+    Box::into_raw(Box::default())
+}
+
+/// Frees a router created with [`matchit_router_new`].
+///
+/// # Safety
+///
+/// `router` must be null or a pointer returned by [`matchit_router_new`] that has not
+/// already been freed, and must not be used by any other thread.
+#[no_mangle]
+pub unsafe extern "C" fn matchit_router_free(router: *mut MatchitRouter) {
+    if !router.is_null() {
+        drop(Box::from_raw(router));
+    }
+}
+
+/// Enables or disables strict validation of inserted routes, see
+/// [`Router::set_strict`].
+///
+/// # Safety
+///
+/// `router` must be a live router that is not used by any other thread.
+#[no_mangle]
+pub unsafe extern "C" fn matchit_router_set_strict(
+    router: *mut MatchitRouter,
+    strict: bool,
+) -> i32 {
+    if router.is_null() {
+        return MATCHIT_ERR_NULL;
+    }
+
+    (*router).0.set_strict(strict);
+    MATCHIT_OK
+}
+
+/// Inserts a route into the router, with the given value.
+///
+/// # Safety
+///
+/// `router` must be a live router that is not used by any other thread, and `route` must
+/// point to `route_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn matchit_insert(
+    router: *mut MatchitRouter,
+    route: *const u8,
+    route_len: usize,
+    value: u64,
+) -> i32 {
+    if router.is_null() || route.is_null() {
+        return MATCHIT_ERR_NULL;
+    }
+
+    let route = match str::from_utf8(slice::from_raw_parts(route, route_len)) {
+        Ok(route) => route,
+        Err(_) => return MATCHIT_ERR_INVALID_UTF8,
+    };
+
+    // A panic must not unwind into the caller.
+    let router = &mut (*router).0;
+    let result = match panic::catch_unwind(AssertUnwindSafe(|| router.insert(route, value))) {
+        Ok(result) => result,
+        Err(_) => return MATCHIT_ERR_PANIC,
+    };
+
+    match result {
+        Ok(()) => MATCHIT_OK,
+        Err(InsertError::Conflict { .. } | InsertError::VersionConflict { .. }) => {
+            MATCHIT_ERR_CONFLICT
+        }
+        Err(InsertError::InvalidParamSegment) => MATCHIT_ERR_INVALID_PARAM_SEGMENT,
+        Err(InsertError::InvalidParam | InsertError::ReservedParam { .. }) => {
+            MATCHIT_ERR_INVALID_PARAM
+        }
+        Err(InsertError::InvalidCatchAll) => MATCHIT_ERR_INVALID_CATCH_ALL,
+        Err(InsertError::IllegalCharacter { .. }) => MATCHIT_ERR_ILLEGAL_CHARACTER,
+        Err(InsertError::TemplateTooLong { .. }) => MATCHIT_ERR_TEMPLATE_TOO_LONG,
+        Err(InsertError::TooManyParams { .. }) => MATCHIT_ERR_TOO_MANY_PARAMS,
+    }
+}
+
+/// Matches the given path, writing the matched value to `value` and the route parameters
+/// to `params`.
+///
+/// `params_len` must hold the capacity of `params`, and is set to the number of route
+/// parameters on success or [`MATCHIT_ERR_BUFFER_TOO_SMALL`]. `params` may be null if the
+/// capacity is zero. The path does not need to be valid UTF-8, and is matched like
+/// [`Router::at_bytes`].
+///
+/// # Safety
+///
+/// `router` must be a live router that is not being modified by any other thread, `path`
+/// must point to `path_len` readable bytes, `value` and `params_len` must be writable, and
+/// `params` must point to `*params_len` writable parameters.
+#[no_mangle]
+pub unsafe extern "C" fn matchit_at(
+    router: *const MatchitRouter,
+    path: *const u8,
+    path_len: usize,
+    value: *mut u64,
+    params: *mut MatchitParam,
+    params_len: *mut usize,
+) -> i32 {
+    if router.is_null() || path.is_null() || value.is_null() || params_len.is_null() {
+        return MATCHIT_ERR_NULL;
+    }
+
+    let capacity = *params_len;
+    if params.is_null() && capacity > 0 {
+        return MATCHIT_ERR_NULL;
+    }
+
+    let path = slice::from_raw_parts(path, path_len);
+
+    // A panic must not unwind into the caller.
+    let router = &(*router).0;
+    let matched = match panic::catch_unwind(AssertUnwindSafe(|| router.at_bytes(path))) {
+        Ok(Ok(matched)) => matched,
+        Ok(Err(_)) => return MATCHIT_ERR_NOT_FOUND,
+        Err(_) => return MATCHIT_ERR_PANIC,
+    };
+
+    let captured = matched.params;
+    *params_len = captured.len();
+    if captured.len() > capacity {
+        return MATCHIT_ERR_BUFFER_TOO_SMALL;
+    }
+
+    for (i, param) in captured.iter().enumerate() {
+        let param = MatchitParam {
+            key: param.key.as_ptr(),
+            key_len: param.key.len(),
+            offset: param.value.as_ptr() as usize - path.as_ptr() as usize,
+            len: param.value.len(),
+        };
+
+        ptr::write(params.add(i), param);
+    }
+
+    *value = *matched.value;
+    MATCHIT_OK
+}
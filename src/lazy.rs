@@ -0,0 +1,87 @@
+//! Values that are built the first time they are matched.
+//!
+//! See [`Router::insert_lazy`](crate::Router::insert_lazy).
+
+use std::fmt;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+type Init<T> = Box<dyn FnOnce() -> T + Send>;
+
+/// A value that is built by a closure the first time it is needed.
+///
+/// Concurrent calls to [`Lazy::force`] are synchronized, so the closure runs exactly once
+/// and every caller observes the same value. If the closure panics, the value remains
+/// uninitialized and later calls to [`Lazy::force`] panic as well.
+pub struct Lazy<T> {
+    value: OnceLock<T>,
+    // Taken by the first call to `force`.
+    init: Mutex<Option<Init<T>>>,
+}
+
+impl<T> Lazy<T> {
+    /// Creates a value that is built by `init` when first needed.
+    pub fn new(init: impl FnOnce() -> T + Send + 'static) -> Self {
+        Self {
+            value: OnceLock::new(),
+            init: Mutex::new(Some(Box::new(init))),
+        }
+    }
+
+    /// Returns the value, building it if it has not been built yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a previous call to the closure panicked.
+    pub fn force(&self) -> &T {
+        self.value.get_or_init(|| {
+            let init = self
+                .init
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+
+            init.expect("lazy value panicked during initialization")()
+        })
+    }
+
+    /// Returns the value if it has been built.
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    /// Returns a mutable reference to the value if it has been built.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.get_mut()
+    }
+
+    /// Returns a mutable reference to the value, building it if it has not been built yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a previous call to the closure panicked.
+    pub fn force_mut(&mut self) -> &mut T {
+        self.force();
+        self.value.get_mut().unwrap()
+    }
+
+    /// Returns `true` if the value has been built.
+    pub fn is_initialized(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Consumes the lazy value, returning the value if it has been built.
+    ///
+    /// The closure is dropped without being called if the value was never needed.
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => f.debug_tuple("Lazy").field(value).finish(),
+            None => f.write_str("Lazy(<uninit>)"),
+        }
+    }
+}
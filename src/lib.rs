@@ -1,8 +1,46 @@
 #![deny(rust_2018_idioms, clippy::all)]
 
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod lazy;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+#[cfg(feature = "nginx")]
+pub mod nginx;
+pub mod pattern;
 pub mod router;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "timing")]
+pub mod timing;
+#[cfg(feature = "trace")]
+pub mod trace;
 pub mod tree;
+#[cfg(feature = "trie-bytes")]
+pub mod trie_bytes;
 
-pub use error::{InsertError, MatchError};
-pub use router::{Match, Param, Router};
+pub use error::{
+    CheckedInsertError, ConfigError, InsertError, MatchError, StripError, TimeoutOrMatchError,
+};
+pub use lazy::Lazy;
+#[cfg(feature = "manifest")]
+pub use manifest::{ManifestParam, ManifestRoute, ParamKind, RouteManifest};
+pub use pattern::Pattern;
+pub use router::{
+    AncestorMatch, DecodedMatch, Match, Param, ParamIdx, Redacted, RouteObserver, Router,
+    RouterBuilder, RouterConfig, RouterSummary, Routes, TrailingSlashBehavior, ValidatedMatch,
+};
+#[cfg(feature = "stats")]
+pub use stats::MatchStats;
+#[cfg(feature = "timing")]
+pub use timing::{Clock, SystemClock, TimedMatch};
+#[cfg(feature = "trace")]
+pub use trace::{MatchTrace, TraceStep};
+pub use tree::{is_valid_template_strict, template_hash, TreeVisitor, TEMPLATE_HASH_VERSION};
+#[cfg(feature = "trie-bytes")]
+pub use trie_bytes::ImportError;
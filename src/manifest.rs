@@ -0,0 +1,133 @@
+use crate::Router;
+
+use serde::Serialize;
+
+/// A serializable description of the routes in a router, returned by
+/// [`Router::manifest`].
+///
+/// This is intended for generating code that depends on the routes in a router, such as
+/// typed link helpers for a frontend. Routes are listed in the same order as
+/// [`Router::routes`], so the serialized manifest is deterministic.
+///
+/// ```
+/// use matchit::Router;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut router = Router::new();
+/// router.insert("/users/{id}", "getUser")?;
+///
+/// let manifest = router.manifest_with(|operation| serde_json::json!({ "operationId": operation }));
+/// assert_eq!(
+///     serde_json::to_value(&manifest)?,
+///     serde_json::json!({
+///         "routes": [{
+///             "template": "/users/{id}",
+///             "params": [{ "name": "id", "kind": "normal" }],
+///             "metadata": { "operationId": "getUser" },
+///         }],
+///     })
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RouteManifest {
+    /// The routes in the router.
+    pub routes: Vec<ManifestRoute>,
+}
+
+/// A single route in a [`RouteManifest`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ManifestRoute {
+    /// The route, as it was inserted into the router.
+    pub template: String,
+
+    /// The route parameters, in the order they appear in the route.
+    pub params: Vec<ManifestParam>,
+
+    /// Extra information about the route, see [`Router::manifest_with`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// A route parameter in a [`ManifestRoute`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ManifestParam {
+    /// The name of the parameter.
+    pub name: String,
+
+    /// The type of the parameter.
+    pub kind: ParamKind,
+}
+
+/// The type of a route parameter.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamKind {
+    /// A route parameter that matches a single segment, e.g. `{id}`.
+    Normal,
+    /// A catch-all parameter that matches the rest of the path, e.g. `{*path}`.
+    CatchAll,
+}
+
+impl<T> Router<T> {
+    /// Returns a manifest of the routes in this router.
+    ///
+    /// See [`RouteManifest`] for details.
+    pub fn manifest(&self) -> RouteManifest {
+        self.manifest_inner(|_| None)
+    }
+
+    /// Returns a manifest of the routes in this router, including metadata for each route
+    /// extracted from its value.
+    ///
+    /// See [`RouteManifest`] for details.
+    pub fn manifest_with<F>(&self, metadata: F) -> RouteManifest
+    where
+        F: Fn(&T) -> serde_json::Value,
+    {
+        self.manifest_inner(|value| Some(metadata(value)))
+    }
+
+    fn manifest_inner<F>(&self, metadata: F) -> RouteManifest
+    where
+        F: Fn(&T) -> Option<serde_json::Value>,
+    {
+        let routes = self
+            .routes()
+            .map(|(template, value)| ManifestRoute {
+                params: params(&template),
+                metadata: metadata(value),
+                template,
+            })
+            .collect();
+
+        RouteManifest { routes }
+    }
+}
+
+// Returns the parameters of the given route.
+fn params(route: &str) -> Vec<ManifestParam> {
+    let mut params = Vec::new();
+    let mut rest = route;
+
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}').unwrap();
+
+        let param = match rest[start + 1..end].strip_prefix('*') {
+            Some(name) => ManifestParam {
+                name: name.to_owned(),
+                kind: ParamKind::CatchAll,
+            },
+            None => ManifestParam {
+                name: rest[start + 1..end].to_owned(),
+                kind: ParamKind::Normal,
+            },
+        };
+
+        params.push(param);
+        rest = &rest[end + 1..];
+    }
+
+    params
+}
@@ -0,0 +1,200 @@
+//! Conversion of nginx `location` blocks to routes.
+//!
+//! Exact (`=`) and prefix (`^~` or no modifier) locations are supported. Regex locations
+//! (`~` and `~*`) and named locations (`@name`) cannot be represented as routes, and are
+//! reported by [`convert`].
+//!
+//! nginx selects the location with the longest matching prefix, which is compensated for
+//! by the generated routes. Prefix locations match by string prefix, so `/api` matches
+//! both `/api/users` and `/apiary`, and is converted to the routes `/api` and
+//! `/api{*rest}`. Routes are then preferred by specificity, which agrees with the longest
+//! prefix for the generated set of routes. Without regex locations, `^~` has no effect on
+//! matching.
+//!
+//! ```
+//! use matchit::nginx::{self, Location, LocationKind, Modifier};
+//! use matchit::Router;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let locations = [
+//!     Location::new(Modifier::Prefix, "/"),
+//!     Location::new(Modifier::Exact, "/api"),
+//!     Location::new(Modifier::PreferPrefix, "/api/"),
+//! ];
+//!
+//! let mut router = Router::new();
+//! for (route, kind) in nginx::convert(&locations)? {
+//!     router.insert(route, kind)?;
+//! }
+//!
+//! assert_eq!(*router.at("/api")?.value, LocationKind::Exact(1));
+//! assert_eq!(*router.at("/api/users")?.value, LocationKind::Prefix(2));
+//! assert_eq!(*router.at("/apiary")?.value, LocationKind::Prefix(0));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+/// The modifier of a `location` block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    /// An exact match, `location = /path`.
+    Exact,
+    /// A prefix match, `location /path`.
+    Prefix,
+    /// A prefix match that disables regex matching, `location ^~ /path`.
+    PreferPrefix,
+    /// A case-sensitive regex match, `location ~ pattern`.
+    Regex,
+    /// A case-insensitive regex match, `location ~* pattern`.
+    RegexCaseInsensitive,
+}
+
+/// An nginx `location` block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// The modifier of the location.
+    pub modifier: Modifier,
+    /// The path, pattern, or name of the location.
+    pub path: String,
+}
+
+impl Location {
+    /// Creates a new location.
+    pub fn new(modifier: Modifier, path: impl Into<String>) -> Self {
+        Self {
+            modifier,
+            path: path.into(),
+        }
+    }
+}
+
+/// Formats the location as it appears in an nginx configuration, without the `location`
+/// directive.
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.modifier {
+            Modifier::Exact => write!(f, "= {}", self.path),
+            Modifier::Prefix => write!(f, "{}", self.path),
+            Modifier::PreferPrefix => write!(f, "^~ {}", self.path),
+            Modifier::Regex => write!(f, "~ {}", self.path),
+            Modifier::RegexCaseInsensitive => write!(f, "~* {}", self.path),
+        }
+    }
+}
+
+/// The location a route was generated from, by its index in the locations passed to
+/// [`convert`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LocationKind {
+    /// The route matches an exact location.
+    Exact(usize),
+    /// The route matches a prefix location.
+    Prefix(usize),
+}
+
+impl LocationKind {
+    /// Returns the index of the location.
+    pub fn index(self) -> usize {
+        match self {
+            Self::Exact(i) | Self::Prefix(i) => i,
+        }
+    }
+}
+
+/// An error returned by [`convert`] for a location that cannot be converted.
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvertError {
+    /// Regex locations cannot be represented as routes.
+    Regex {
+        /// The location, as it appears in the configuration.
+        location: String,
+    },
+    /// The location is a named location, or its path contains characters that are
+    /// interpreted as route parameters.
+    UnsupportedPath {
+        /// The location, as it appears in the configuration.
+        location: String,
+    },
+    /// The location is defined more than once.
+    Duplicate {
+        /// The location, as it appears in the configuration.
+        location: String,
+    },
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Regex { location } => {
+                write!(f, "Regex location `{}` cannot be converted", location)
+            }
+            Self::UnsupportedPath { location } => {
+                write!(
+                    f,
+                    "Location `{}` cannot be represented as a route",
+                    location
+                )
+            }
+            Self::Duplicate { location } => write!(f, "Duplicate location `{}`", location),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// Converts the given locations to routes, returning the first location that cannot be
+/// converted.
+///
+/// Exact locations are converted to a single route. Prefix locations are converted to the
+/// prefix itself, unless an exact location has the same path, and the prefix followed by
+/// a catch-all parameter named `rest`. Inserting every route into a router matches paths
+/// to the same locations as nginx.
+pub fn convert(locations: &[Location]) -> Result<Vec<(String, LocationKind)>, ConvertError> {
+    let mut exact = HashSet::new();
+    let mut prefixes = HashSet::new();
+
+    for location in locations {
+        let seen = match location.modifier {
+            Modifier::Exact => &mut exact,
+            Modifier::Prefix | Modifier::PreferPrefix => &mut prefixes,
+            Modifier::Regex | Modifier::RegexCaseInsensitive => {
+                return Err(ConvertError::Regex {
+                    location: location.to_string(),
+                })
+            }
+        };
+
+        if !location.path.starts_with('/') || location.path.contains(['{', '}']) {
+            return Err(ConvertError::UnsupportedPath {
+                location: location.to_string(),
+            });
+        }
+
+        if !seen.insert(location.path.as_str()) {
+            return Err(ConvertError::Duplicate {
+                location: location.to_string(),
+            });
+        }
+    }
+
+    let mut routes = Vec::new();
+    for (i, location) in locations.iter().enumerate() {
+        let path = &location.path;
+
+        if location.modifier == Modifier::Exact {
+            routes.push((path.clone(), LocationKind::Exact(i)));
+            continue;
+        }
+
+        if !exact.contains(path.as_str()) {
+            routes.push((path.clone(), LocationKind::Prefix(i)));
+        }
+
+        routes.push((format!("{}{{*rest}}", path), LocationKind::Prefix(i)));
+    }
+
+    Ok(routes)
+}
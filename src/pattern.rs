@@ -0,0 +1,97 @@
+use crate::tree::Node;
+use crate::{InsertError, Param};
+
+/// A single route that can be matched against paths without building a [`Router`](crate::Router).
+///
+/// Patterns use the same matching logic as a router containing only this route.
+///
+/// ```
+/// use matchit::Pattern;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let pattern = Pattern::new("/users/{id}")?;
+///
+/// let params = pattern.match_path("/users/978").unwrap();
+/// assert_eq!(params[0].value, b"978");
+///
+/// assert!(pattern.match_path("/users").is_none());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    route: String,
+    node: Node<()>,
+}
+
+impl Pattern {
+    /// Parses the given route.
+    pub fn new(route: impl Into<String>) -> Result<Self, InsertError> {
+        let route = route.into();
+        let mut node = Node::default();
+        node.insert(route.clone(), ())?;
+        Ok(Self { route, node })
+    }
+
+    /// Returns the route this pattern was created from.
+    pub fn as_str(&self) -> &str {
+        &self.route
+    }
+
+    /// Matches the given path against this pattern, returning the route parameters
+    /// if it matches.
+    pub fn match_path<'path>(&self, path: &'path str) -> Option<Vec<Param<'_, 'path>>> {
+        self.node
+            .search(path.as_bytes(), Vec::new(), &mut ())
+            .ok()
+            .map(|(_, params)| params)
+    }
+
+    /// Builds a path from this pattern, replacing each route parameter with the value of
+    /// the parameter with the same key.
+    ///
+    /// Returns `None` if a parameter is missing, or if the path would not match this
+    /// pattern with exactly the same parameters, such as a route parameter containing a
+    /// `/` or an empty catch-all parameter. Values are inserted as-is, so building a path
+    /// from the parameters of a match reproduces the original path byte for byte. In
+    /// particular, catch-all parameters capture any trailing slash.
+    ///
+    /// ```
+    /// use matchit::Pattern;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let pattern = Pattern::new("/files/{*path}")?;
+    ///
+    /// let params = pattern.match_path("/files/a/").unwrap();
+    /// assert_eq!(params[0].value, b"a/");
+    /// assert_eq!(pattern.url_for(&params).unwrap(), "/files/a/");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn url_for(&self, params: &[Param<'_, '_>]) -> Option<String> {
+        let mut path = Vec::with_capacity(self.route.len());
+        let mut rest = self.route.as_bytes();
+
+        while let Some(start) = rest.iter().position(|&c| c == b'{') {
+            let end = start + rest[start..].iter().position(|&c| c == b'}')?;
+            let key = rest[start + 1..end]
+                .strip_prefix(b"*")
+                .unwrap_or(&rest[start + 1..end]);
+            let param = params.iter().find(|param| param.key == key)?;
+
+            path.extend_from_slice(&rest[..start]);
+            path.extend_from_slice(param.value);
+            rest = &rest[end + 1..];
+        }
+
+        path.extend_from_slice(rest);
+        let path = String::from_utf8(path).ok()?;
+
+        // Ensure the path matches this pattern with the same parameters.
+        let matched = self.match_path(&path)?;
+        let same = matched.iter().all(|matched| {
+            let param = params.iter().find(|param| param.key == matched.key);
+            param.map(|param| param.value) == Some(matched.value)
+        });
+
+        same.then_some(path)
+    }
+}
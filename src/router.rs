@@ -1,80 +1,2932 @@
-use crate::tree::Node;
-use crate::{InsertError, MatchError};
+use crate::tree::{self, Node, NodeType, ParamBuffer, SkipCatchAll, Tracer, TreeVisitor};
+use crate::{
+    CheckedInsertError, ConfigError, InsertError, Lazy, MatchError, StripError, TimeoutOrMatchError,
+};
+
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+use std::iter::FusedIterator;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{mem, str};
 
 /// A zero-copy URL router.
 ///
 /// See [the crate documentation](crate) for details.
-#[derive(Clone, Debug)]
 pub struct Router<T> {
     pub root: Node<T>,
+    // Incremented on every successful insertion or removal.
+    generation: u64,
+    // Notified of every successful insertion or removal.
+    observer: Option<Box<dyn RouteObserver<T> + Send + Sync>>,
+    // Whether routes are validated against the RFC 3986 path grammar.
+    strict: bool,
+    // Routes inserted with `insert_versioned`, by version.
+    versions: BTreeMap<u32, Node<T>>,
+    // Routes inserted with `insert_version_range`, without the version prefix, along
+    // with the values for each range of versions sorted by the first version.
+    version_ranges: Node<Vec<(RangeInclusive<u32>, T)>>,
+    // The prefix of the version in the first segment of a path, see `set_version_prefix`.
+    version_prefix: String,
+    // How trailing slashes are handled when matching.
+    trailing_slash: TrailingSlashBehavior,
+    // The maximum length of an inserted route, see `set_max_template_len`.
+    max_template_len: usize,
+    // The maximum number of parameters in an inserted route, see `set_max_params`.
+    max_params: usize,
+    // The number of routes with route parameters, see `has_dynamic_routes`.
+    dynamic_routes: usize,
+}
+
+/// A source of routes for [`Router::build_from_config`].
+///
+/// This separates loading routes, for example from a configuration file, from building
+/// the router.
+pub trait RouterConfig<T> {
+    /// The routes and their values.
+    type Routes: IntoIterator<Item = (String, T)>;
+
+    /// Returns the routes to insert, in order.
+    fn routes(self) -> Self::Routes;
+}
+
+/// A builder for a [`Router`], returned by [`Router::builder`].
+///
+/// Routes are buffered and only inserted by [`RouterBuilder::build`], which reports every
+/// route that could not be inserted rather than stopping at the first error.
+///
+/// ```
+/// use matchit::{InsertError, Router, TrailingSlashBehavior};
+///
+/// let router = Router::builder()
+///     .with_trailing_slash_behavior(TrailingSlashBehavior::Accept)
+///     .insert("/users", "Users")
+///     .insert("/users/{id}", "A User")
+///     .build()
+///     .unwrap();
+/// assert_eq!(*router.at("/users/").unwrap().value, "Users");
+///
+/// let errors = Router::builder()
+///     .insert("/users/{id}", "A User")
+///     .insert("/users/{name}", "A User")
+///     .insert("/users/{id", "A User")
+///     .build()
+///     .unwrap_err();
+/// assert!(matches!(errors[..], [InsertError::Conflict { .. }, InsertError::InvalidParam]));
+/// ```
+pub struct RouterBuilder<T> {
+    // The router the routes are inserted into, holding the configuration.
+    router: Router<T>,
+    routes: Vec<(String, T)>,
+}
+
+impl<T> RouterBuilder<T> {
+    /// Creates a builder with the default configuration and no routes.
+    pub fn new() -> Self {
+        Self {
+            router: Router::new(),
+            routes: Vec::new(),
+        }
+    }
+
+    /// Sets how trailing slashes are handled, see
+    /// [`Router::with_trailing_slash_behavior`].
+    pub fn with_trailing_slash_behavior(mut self, behavior: TrailingSlashBehavior) -> Self {
+        self.router.trailing_slash = behavior;
+        self
+    }
+
+    /// Enables or disables strict validation of routes, see [`Router::set_strict`].
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.router.set_strict(strict);
+        self
+    }
+
+    /// Sets the maximum length of a route, see [`Router::set_max_template_len`].
+    pub fn with_max_template_len(mut self, max: usize) -> Self {
+        self.router.set_max_template_len(max);
+        self
+    }
+
+    /// Sets the maximum number of parameters in a route, see [`Router::set_max_params`].
+    pub fn with_max_params(mut self, max: usize) -> Self {
+        self.router.set_max_params(max);
+        self
+    }
+
+    /// Adds a route to be inserted by [`RouterBuilder::build`].
+    pub fn insert(mut self, route: impl Into<String>, value: T) -> Self {
+        self.routes.push((route.into(), value));
+        self
+    }
+
+    /// Inserts the routes in order, returning the router, or the errors for every route
+    /// that could not be inserted.
+    ///
+    /// Routes that fail do not prevent later routes from being inserted, so a route that
+    /// conflicts with an earlier failed route is not reported.
+    pub fn build(self) -> Result<Router<T>, Vec<InsertError>> {
+        let mut router = self.router;
+
+        let mut errors = Vec::new();
+        for (route, value) in self.routes {
+            if let Err(error) = router.insert(route, value) {
+                errors.push(error);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(router)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl<T> Default for RouterBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RouterBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterBuilder")
+            .field("routes", &self.routes)
+            .finish_non_exhaustive()
+    }
 }
 
+/// An observer of route changes, see [`Router::set_observer`].
+///
+/// Callbacks are invoked synchronously after a successful mutation, and not for failed
+/// insertions or removals of routes that do not exist. All methods default to doing
+/// nothing.
+///
+/// The router is updated before the observer is called, so a panic in a callback
+/// propagates to the caller of the mutating method but does not undo the mutation.
+pub trait RouteObserver<T> {
+    /// Called after a route is inserted.
+    fn on_insert(&mut self, _route: &str, _value: &T) {}
+
+    /// Called after a route is removed.
+    fn on_remove(&mut self, _route: &str, _value: &T) {}
+}
+
+/// The default maximum length of an inserted route, see [`Router::set_max_template_len`].
+const DEFAULT_MAX_TEMPLATE_LEN: usize = 8 * 1024;
+
 impl<T> Default for Router<T> {
     fn default() -> Self {
         Self {
             root: Node::default(),
+            generation: 0,
+            observer: None,
+            strict: false,
+            versions: BTreeMap::new(),
+            version_ranges: Node::default(),
+            version_prefix: "v".to_owned(),
+            trailing_slash: TrailingSlashBehavior::Strict,
+            max_template_len: DEFAULT_MAX_TEMPLATE_LEN,
+            max_params: tree::MAX_PARAMS,
+            dynamic_routes: 0,
+        }
+    }
+}
+
+/// Observers are not cloned, the returned router does not have an observer.
+impl<T: Clone> Clone for Router<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            generation: self.generation,
+            observer: None,
+            strict: self.strict,
+            versions: self.versions.clone(),
+            version_ranges: self.version_ranges.clone(),
+            version_prefix: self.version_prefix.clone(),
+            trailing_slash: self.trailing_slash,
+            max_template_len: self.max_template_len,
+            max_params: self.max_params,
+            dynamic_routes: self.dynamic_routes,
         }
     }
 }
 
+impl<T: fmt::Debug> fmt::Debug for Router<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("root", &self.root)
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
 impl<T> Router<T> {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Returns a copy of the router, cloning every value.
+    ///
+    /// This is the same as [`Clone::clone`], but makes the cost explicit at the call site:
+    /// the entire tree is copied and each value is cloned individually. Validators set with
+    /// [`Router::insert_with_validator`] are reference-counted, so the copy shares them with
+    /// the original. As with [`Clone::clone`], the observer is not copied, so the copy has
+    /// no observer.
+    pub fn deep_clone(&self) -> Router<T>
+    where
+        T: Clone,
+    {
+        self.clone()
+    }
+
+    /// Creates a router from the given routes and values, returning the first error if any
+    /// route cannot be inserted.
+    ///
+    /// Router does not implement [`FromIterator`] as insertions can fail.
+    ///
+    /// ```
+    /// use matchit::{InsertError, Router};
+    ///
+    /// let router = Router::from_iter([("/users", "Users"), ("/users/{id}", "A User")]).unwrap();
+    /// assert_eq!(*router.at("/users/978").unwrap().value, "A User");
+    ///
+    /// let result = Router::from_iter([("/users/{id}", ()), ("/users/{name}", ())]);
+    /// assert!(matches!(result, Err(InsertError::Conflict { .. })));
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_iter<I, R>(iter: I) -> Result<Self, InsertError>
+    where
+        I: IntoIterator<Item = (R, T)>,
+        R: Into<String>,
+    {
+        let mut router = Self::new();
+        for (route, value) in iter {
+            router.insert(route, value)?;
+        }
+
+        Ok(router)
+    }
+
+    /// Returns a builder for a router, see [`RouterBuilder`].
+    pub fn builder() -> RouterBuilder<T> {
+        RouterBuilder::new()
+    }
+
+    /// Creates a router from the routes described by a configuration, see
+    /// [`RouterConfig`].
+    ///
+    /// ```
+    /// use matchit::{ConfigError, Router, RouterConfig};
+    ///
+    /// // Routes loaded from a configuration file, mapped to upstream services.
+    /// struct Upstreams(Vec<(String, String)>);
+    ///
+    /// impl RouterConfig<String> for Upstreams {
+    ///     type Routes = Vec<(String, String)>;
+    ///
+    ///     fn routes(self) -> Self::Routes {
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// let config = Upstreams(vec![("/users/{id}".to_owned(), "users:8080".to_owned())]);
+    /// let router = Router::build_from_config(config).unwrap();
+    /// assert_eq!(router.at("/users/978").unwrap().value, "users:8080");
+    ///
+    /// let config = Upstreams(vec![("/users/{id".to_owned(), "users:8080".to_owned())]);
+    /// let err = Router::build_from_config(config).unwrap_err();
+    /// assert!(matches!(err, ConfigError::Insert { route, .. } if route == "/users/{id"));
+    /// ```
+    pub fn build_from_config<C>(config: C) -> Result<Self, ConfigError>
+    where
+        C: RouterConfig<T>,
+    {
+        let mut router = Self::new();
+        for (route, value) in config.routes() {
+            if let Err(error) = router.insert(route.clone(), value) {
+                return Err(ConfigError::Insert { route, error });
+            }
+        }
+
+        Ok(router)
+    }
+
+    /// Sets how trailing slashes are handled when matching paths, see
+    /// [`TrailingSlashBehavior`].
+    ///
+    /// The behavior applies to every method that matches paths like [`Router::at`],
+    /// including [`Router::at_mut`], [`Router::value_at`], [`Router::contains_path`],
+    /// [`Router::at_pattern`], [`Router::at_cb`], and [`Router::at_validated`]. Methods that
+    /// inspect a single search of the tree, such as [`Router::at_timeout`], match paths
+    /// exactly as given, as noted in their documentation.
+    ///
+    /// ```
+    /// use matchit::{MatchError, Router, TrailingSlashBehavior};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new().with_trailing_slash_behavior(TrailingSlashBehavior::Redirect);
+    /// router.insert("/users", "Users")?;
+    ///
+    /// assert_eq!(*router.at("/users")?.value, "Users");
+    /// assert_eq!(router.at("/users/").unwrap_err(), MatchError::ExtraTrailingSlash);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_trailing_slash_behavior(mut self, behavior: TrailingSlashBehavior) -> Self {
+        self.trailing_slash = behavior;
+        self
+    }
+
+    /// Inserts a route into the router.
+    ///
+    /// If the insertion fails, `value` is dropped only after the router has been restored,
+    /// so a panic while dropping it cannot leave the router in an inconsistent state.
     pub fn insert(&mut self, route: impl Into<String>, value: T) -> Result<(), InsertError> {
-        self.root.insert(route.into(), value)
+        let route = route.into();
+        self.validate(&route)?;
+
+        let generation = self.generation + 1;
+        let dynamic = route.contains('{');
+
+        match self.observer {
+            Some(ref mut observer) => {
+                self.root
+                    .insert_with_generation(route.clone(), value, generation)?;
+                self.generation = generation;
+                self.dynamic_routes += usize::from(dynamic);
+
+                let value = self.root.route_value(route.clone()).unwrap();
+                observer.on_insert(&route, value);
+            }
+            None => {
+                self.root.insert_with_generation(route, value, generation)?;
+                self.generation = generation;
+                self.dynamic_routes += usize::from(dynamic);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a route into the router, reporting errors with [`CheckedInsertError`].
+    ///
+    /// Syntax errors are reported with their position in the route. Routes are also checked
+    /// more strictly than by [`Router::insert`], which accepts empty routes and duplicate
+    /// parameter names.
+    ///
+    /// ```
+    /// use matchit::{CheckedInsertError, Router};
+    ///
+    /// let mut router = Router::new();
+    /// assert_eq!(
+    ///     router.insert_checked("/users/{id}/files/{id}", ()),
+    ///     Err(CheckedInsertError::DuplicateParameterName {
+    ///         name: "id".to_owned(),
+    ///         first_pos: 7,
+    ///         second_pos: 18,
+    ///     })
+    /// );
+    /// assert_eq!(
+    ///     router.insert_checked("/users/{id", ()),
+    ///     Err(CheckedInsertError::UnclosedBrace { pos: 7 })
+    /// );
+    /// ```
+    pub fn insert_checked(
+        &mut self,
+        route: impl Into<String>,
+        value: T,
+    ) -> Result<(), CheckedInsertError> {
+        let route = route.into();
+
+        // Check the limits first, which bounds the cost of checking the syntax.
+        self.validate(&route)?;
+        tree::check_syntax(&route)?;
+
+        Ok(self.insert(route, value)?)
+    }
+
+    /// Sets an observer that is notified of every route that is inserted into or removed
+    /// from this router, replacing any existing observer.
+    pub fn set_observer(&mut self, observer: Box<dyn RouteObserver<T> + Send + Sync>) {
+        self.observer = Some(observer);
     }
 
+    /// Enables or disables strict validation of inserted routes.
+    ///
+    /// In strict mode, [`Router::insert`] rejects routes containing characters that are not
+    /// allowed in a URI path by RFC 3986, outside of route parameters, with
+    /// [`InsertError::IllegalCharacter`]. Percent-encoded sequences must also be
+    /// well-formed. Routers are permissive by default.
+    ///
+    /// ```
+    /// use matchit::{InsertError, Router};
+    ///
+    /// let mut router = Router::new();
+    /// router.set_strict(true);
+    ///
+    /// assert!(router.insert("/users/{name}/~profile", ()).is_ok());
+    /// assert_eq!(
+    ///     router.insert("/search results", ()),
+    ///     Err(InsertError::IllegalCharacter { ch: ' ', at: 7 })
+    /// );
+    /// ```
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Sets the maximum length of an inserted route in bytes, which defaults to 8 KiB.
+    ///
+    /// Longer routes are rejected with [`InsertError::TemplateTooLong`] before any other
+    /// validation, so that generated routes cannot make insertion arbitrarily slow.
+    ///
+    /// ```
+    /// use matchit::{InsertError, Router};
+    ///
+    /// let mut router = Router::new();
+    /// router.set_max_template_len(8);
+    ///
+    /// assert!(router.insert("/users", ()).is_ok());
+    /// assert_eq!(
+    ///     router.insert("/users/{id}", ()),
+    ///     Err(InsertError::TemplateTooLong { len: 11, max: 8 })
+    /// );
+    /// ```
+    pub fn set_max_template_len(&mut self, max: usize) {
+        self.max_template_len = max;
+    }
+
+    /// Sets the maximum number of parameters in an inserted route, including any catch-all
+    /// parameter.
+    ///
+    /// Routes with more parameters are rejected with [`InsertError::TooManyParams`]. Routes
+    /// can never have more than 27 parameters, which is also the default.
+    pub fn set_max_params(&mut self, max: usize) {
+        self.max_params = max;
+    }
+
+    // Validates a route against the limits and, in strict mode, the RFC 3986 grammar.
+    fn validate(&self, route: &str) -> Result<(), InsertError> {
+        tree::validate_limits(route, self.max_template_len, self.max_params)?;
+
+        if self.strict {
+            tree::validate_strict(route)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the observer set with [`Router::set_observer`], returning it.
+    pub fn take_observer(&mut self) -> Option<Box<dyn RouteObserver<T> + Send + Sync>> {
+        self.observer.take()
+    }
+
+    /// Tries to find a value in the router matching the given path.
+    ///
+    /// Paths that only differ from a route by a trailing slash are handled according to
+    /// the router's [`TrailingSlashBehavior`]. If no route inserted with [`Router::insert`]
+    /// matches the path, the routes inserted with [`Router::insert_version_range`] are
+    /// tried instead.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// let matched = router.at("/users/978")?;
+    /// assert_eq!(*matched.value, "A User");
+    /// assert_eq!(matched.params[0].value, b"978");
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn at<'path>(&self, path: &'path str) -> Result<Match<'_, 'path, &T>, MatchError> {
-        match self.root.at(path.as_bytes()) {
-            Ok((value, params)) => Ok(Match {
-                // Safety: We only expose `&mut T` through `&mut self`
-                value: unsafe { &*value.get() },
-                params,
-            }),
-            Err(e) => Err(e),
+        self.at_bytes(path.as_bytes())
+    }
+
+    /// Tries to find a value in the router matching the given path, which does not need to
+    /// be valid UTF-8.
+    ///
+    /// Routes are always valid UTF-8, but paths can be matched before they are validated,
+    /// in which case any invalid bytes are captured by route parameters.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/files/{name}", "A File")?;
+    ///
+    /// let matched = router.at_bytes(b"/files/\xff")?;
+    /// assert_eq!(*matched.value, "A File");
+    /// assert_eq!(matched.params[0].value, b"\xff");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_bytes<'path>(&self, path: &'path [u8]) -> Result<Match<'_, 'path, &T>, MatchError> {
+        let (target, params) = self.lookup(path)?;
+        Ok(target.to_match(target.value(), params))
+    }
+
+    // Finds the route matching the given path, applying the trailing slash behavior and
+    // falling back to the routes inserted with `insert_version_range`.
+    //
+    // Every method that matches paths like `Router::at` goes through this, or through
+    // `Router::lookup_miss` after searching the main tree itself.
+    fn lookup<'path>(&self, path: &'path [u8]) -> Result<Found<'_, 'path, T>, MatchError> {
+        match self.root.search(path, Vec::new(), &mut ()) {
+            Ok((node, params)) => {
+                self.check_trailing_slash(path, &node.route)?;
+                Ok((Target::Node(node), params))
+            }
+            Err(_) => self.lookup_miss(path),
         }
     }
 
-    pub fn at_mut<'path>(
+    // Continues `Router::lookup` after the path did not match the main tree.
+    fn lookup_miss<'path>(&self, path: &'path [u8]) -> Result<Found<'_, 'path, T>, MatchError> {
+        if let Some((target, params)) = self.at_version_range(path) {
+            self.check_trailing_slash(path, target.route())?;
+            return Ok((target, params));
+        }
+
+        let trailing_slash = path.len() > 1 && path.ends_with(b"/");
+        match self.trailing_slash {
+            TrailingSlashBehavior::Accept => {
+                self.at_toggled_slash(path).ok_or(MatchError::NotFound)
+            }
+            TrailingSlashBehavior::Redirect => match self.at_toggled_slash(path) {
+                Some(_) if trailing_slash => Err(MatchError::ExtraTrailingSlash),
+                Some(_) => Err(MatchError::MissingTrailingSlash),
+                None => Err(MatchError::NotFound),
+            },
+            TrailingSlashBehavior::Strict | TrailingSlashBehavior::Reject => {
+                Err(MatchError::NotFound)
+            }
+        }
+    }
+
+    // Returns an error if the path has a trailing slash that the matched route does not,
+    // and the router rejects such paths.
+    fn check_trailing_slash(&self, path: &[u8], route: &str) -> Result<(), MatchError> {
+        let rejected = self.trailing_slash == TrailingSlashBehavior::Reject
+            && path.len() > 1
+            && path.ends_with(b"/")
+            && !route.ends_with('/');
+
+        if rejected {
+            return Err(MatchError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    // Matches the path exactly as given.
+    fn at_exact<'path>(&self, path: &'path [u8]) -> Option<Found<'_, 'path, T>> {
+        match self.root.search(path, Vec::new(), &mut ()) {
+            Ok((node, params)) => Some((Target::Node(node), params)),
+            Err(_) => self.at_version_range(path),
+        }
+    }
+
+    // Matches the path with its trailing slash removed, or with a trailing slash added.
+    fn at_toggled_slash<'path>(&self, path: &'path [u8]) -> Option<Found<'_, 'path, T>> {
+        if path.len() > 1 && path.ends_with(b"/") {
+            return self.at_exact(&path[..path.len() - 1]);
+        }
+
+        let mut toggled = path.to_vec();
+        toggled.push(b'/');
+        let (target, toggled_params) = self.at_exact(&toggled)?;
+
+        // The added slash is never part of a parameter, so the parameters can be borrowed
+        // from the original path instead.
+        let mut params = Vec::with_capacity(toggled_params.len());
+        for param in toggled_params {
+            let start = param.value.as_ptr() as usize - toggled.as_ptr() as usize;
+            params.push(Param {
+                key: param.key,
+                value: path.get(start..start + param.value.len())?,
+            });
+        }
+
+        Some((target, params))
+    }
+
+    /// Tries to find a value in the router matching the given path, treating encoded
+    /// slashes (`%2F` or `%2f`) as path separators, and decoding them in route parameter
+    /// values.
+    ///
+    /// The path is first matched with encoded slashes decoded, so that `/bucket%2Ffile`
+    /// matches `/{bucket}/{*key}`. If no route matches, the path is matched as given, so
+    /// that a route parameter can capture a value containing encoded slashes, and
+    /// `/bucket/dir%2Ffile` matches `/bucket/{key}`. In both cases, the decoded value of
+    /// each route parameter is returned in [`DecodedMatch::values`], such as `dir/file`
+    /// for `key`. No other percent-encoded sequences are decoded.
+    ///
+    /// # Security
+    ///
+    /// Encoded slashes are usually treated as part of a segment so that user-provided
+    /// values cannot introduce new path segments. With this method, a value such as
+    /// `..%2F..%2Fsecret` spans multiple segments, and may match catch-all routes or
+    /// routes with `..` segments. Decoded parameter values may also contain `/` and `..`
+    /// segments, so any parameter used to access the file system must be validated.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/bucket/{key}", "An Object")?;
+    ///
+    /// let decoded = router.at_encoded_slash("/bucket/dir%2Ffile")?;
+    /// assert_eq!(*decoded.matched.value, "An Object");
+    /// assert_eq!(decoded.matched.params[0].value, b"dir%2Ffile");
+    /// assert_eq!(decoded.values[0], "dir/file");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_encoded_slash<'path>(
+        &self,
+        path: &'path str,
+    ) -> Result<DecodedMatch<'_, 'path, &T>, MatchError> {
+        let matched = match self.at_encoded_separators(path) {
+            Some(matched) => matched,
+            None => self.at(path)?,
+        };
+
+        // Parameter values are subslices of the path that end at a `/`, an encoded slash,
+        // or the end of the path, so they are always valid UTF-8.
+        let values = matched
+            .params
+            .iter()
+            .map(|param| decode_slashes(str::from_utf8(param.value).unwrap()))
+            .collect();
+
+        Ok(DecodedMatch { matched, values })
+    }
+
+    // Matches the path with encoded slashes decoded, with route parameters borrowed from
+    // the path as given. Returns `None` if the path does not contain an encoded slash or
+    // does not match.
+    fn at_encoded_separators<'path>(&self, path: &'path str) -> Option<Match<'_, 'path, &T>> {
+        let bytes = path.as_bytes();
+
+        // The position of each decoded byte in the original path.
+        let mut positions = Vec::with_capacity(bytes.len() + 1);
+        let mut decoded = Vec::with_capacity(bytes.len());
+
+        let mut i = 0;
+        while i < bytes.len() {
+            positions.push(i);
+            if bytes[i..].starts_with(b"%2F") || bytes[i..].starts_with(b"%2f") {
+                decoded.push(b'/');
+                i += 3;
+            } else {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+        positions.push(bytes.len());
+
+        if decoded.len() == bytes.len() {
+            return None;
+        }
+
+        let matched = self.at_bytes(&decoded).ok()?;
+
+        // Map the parameters back to the original path.
+        let params = matched
+            .params
+            .iter()
+            .map(|param| {
+                let start = param.value.as_ptr() as usize - decoded.as_ptr() as usize;
+                let end = start + param.value.len();
+                Param {
+                    key: param.key,
+                    value: &bytes[positions[start]..positions[end]],
+                }
+            })
+            .collect();
+
+        Some(Match {
+            value: matched.value,
+            params,
+            generation: matched.generation,
+            template_hash: matched.template_hash,
+        })
+    }
+
+    /// Inserts a route into the router under the given version.
+    ///
+    /// Versioned routes are kept separately from routes inserted with [`Router::insert`],
+    /// and can only be matched with [`Router::at_with_version`]. The same route can be
+    /// inserted under multiple versions. The observer is not notified of versioned routes.
+    pub fn insert_versioned(
+        &mut self,
+        version: u32,
+        route: impl Into<String>,
+        value: T,
+    ) -> Result<(), InsertError> {
+        let route = route.into();
+        self.validate(&route)?;
+
+        let generation = self.generation + 1;
+        self.versions
+            .entry(version)
+            .or_default()
+            .insert_with_generation(route, value, generation)?;
+        self.generation = generation;
+
+        Ok(())
+    }
+
+    /// Inserts a group of routes into the router under the given version, with `prefix`
+    /// prepended to each route.
+    ///
+    /// The prefix is prepended as-is, so `/api` and `/users/{id}` are inserted as
+    /// `/api/users/{id}`. The routes are matched with [`Router::at_with_version`], see
+    /// [`Router::insert_versioned`].
+    ///
+    /// If any route cannot be inserted, the routes of the group that were already inserted
+    /// are removed before the error is returned, leaving the router unchanged.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert_versioned_group(1, "/api", [("/users", "Users"), ("/users/{id}", "A User")])?;
+    /// router.insert_versioned_group(2, "/api", [("/users/{id}", "A User (v2)")])?;
+    ///
+    /// assert_eq!(*router.at_with_version(1, "/api/users/978")?.value, "A User");
+    /// assert_eq!(*router.at_with_version(2, "/api/users/978")?.value, "A User (v2)");
+    ///
+    /// // Routes fall back to earlier versions.
+    /// assert_eq!(*router.at_with_version(2, "/api/users")?.value, "Users");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert_versioned_group<R>(
+        &mut self,
+        version: u32,
+        prefix: &str,
+        routes: impl IntoIterator<Item = (R, T)>,
+    ) -> Result<(), InsertError>
+    where
+        R: Into<String>,
+    {
+        let generation = self.generation;
+        let existed = self.versions.contains_key(&version);
+        let mut inserted = Vec::new();
+
+        for (route, value) in routes {
+            let route = format!("{}{}", prefix, route.into());
+
+            if let Err(err) = self.insert_versioned(version, route.clone(), value) {
+                let mut removed = Vec::new();
+                if let Some(node) = self.versions.get_mut(&version) {
+                    removed.extend(inserted.into_iter().map(|route| node.remove(route)));
+
+                    if !existed && node.priority == 0 {
+                        self.versions.remove(&version);
+                    }
+                }
+
+                self.generation = generation;
+
+                // Drop the removed values only after the router has been restored.
+                drop(removed);
+                return Err(err);
+            }
+
+            inserted.push(route);
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a route into the router for a range of versions, with the given value.
+    ///
+    /// The version is taken from the first segment of a path, which consists of the
+    /// version prefix, `v` by default, followed by the version number without leading
+    /// zeros. For example, a route `/users/{id}` inserted for versions `2..=4` matches
+    /// `/v2/users/1`, `/v3/users/1` and `/v4/users/1`, with the version captured as a
+    /// route parameter named `version` before any other route parameters. Routes cannot
+    /// declare a parameter named `version` themselves, see [`InsertError::ReservedParam`].
+    ///
+    /// These routes are matched by [`Router::at`] and [`Router::at_bytes`] if no other
+    /// route matches the path. The same route can be inserted for multiple ranges of
+    /// versions as long as they do not overlap, see [`InsertError::VersionConflict`].
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert_version_range("/users/{id}", "Old User", 1..=2)?;
+    /// router.insert_version_range("/users/{id}", "User", 3..=5)?;
+    ///
+    /// let matched = router.at("/v4/users/978")?;
+    /// assert_eq!(*matched.value, "User");
+    /// assert_eq!(matched.params[0].value, b"4");
+    /// assert_eq!(matched.params[1].value, b"978");
+    ///
+    /// assert_eq!(*router.at("/v1/users/978")?.value, "Old User");
+    /// assert!(router.at("/v6/users/978").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range of versions is empty.
+    pub fn insert_version_range(
+        &mut self,
+        route: impl Into<String>,
+        value: T,
+        versions: RangeInclusive<u32>,
+    ) -> Result<(), InsertError> {
+        assert!(!versions.is_empty(), "empty version range");
+
+        let mut route = route.into();
+        if !route.starts_with('/') {
+            route.insert(0, '/');
+        }
+
+        self.validate(&route)?;
+
+        // The `version` parameter is captured from the version prefix.
+        let declares_version = route
+            .split('{')
+            .skip(1)
+            .filter_map(|param| param.split('}').next())
+            .any(|name| name.trim_start_matches('*') == "version");
+
+        if declares_version {
+            return Err(InsertError::ReservedParam {
+                name: "version".to_owned(),
+            });
+        }
+
+        let generation = self.generation + 1;
+        match self.version_ranges.route_value_mut(route.clone()) {
+            Some(ranges) => {
+                let overlapping = ranges
+                    .iter()
+                    .find(|(r, _)| r.start() <= versions.end() && versions.start() <= r.end());
+
+                if let Some((existing, _)) = overlapping {
+                    return Err(InsertError::VersionConflict {
+                        route,
+                        existing: (*existing.start(), *existing.end()),
+                        inserted: (*versions.start(), *versions.end()),
+                    });
+                }
+
+                let i = ranges.partition_point(|(r, _)| r.start() < versions.start());
+                ranges.insert(i, (versions, value));
+            }
+            None => {
+                let ranges = vec![(versions, value)];
+                self.version_ranges
+                    .insert_with_generation(route, ranges, generation)?;
+            }
+        }
+
+        self.generation = generation;
+        Ok(())
+    }
+
+    /// Sets the prefix of the version in the first segment of a path, for routes inserted
+    /// with [`Router::insert_version_range`].
+    ///
+    /// The default prefix is `v`, as in `/v2/users`.
+    pub fn set_version_prefix(&mut self, prefix: impl Into<String>) {
+        self.version_prefix = prefix.into();
+    }
+
+    /// Returns an iterator over the routes inserted with [`Router::insert_version_range`],
+    /// with the range of versions for each value, in sorted order.
+    pub fn version_ranges(&self) -> impl Iterator<Item = (String, RangeInclusive<u32>, &T)> {
+        let routes = (0..self.version_ranges.priority as usize).map(|n| {
+            let (route, node) = self.version_ranges.nth_route(n).unwrap();
+            (String::from_utf8(route).unwrap(), node.value_ref().unwrap())
+        });
+
+        routes.flat_map(|(route, ranges)| {
+            ranges
+                .iter()
+                .map(move |(versions, value)| (route.clone(), versions.clone(), value))
+        })
+    }
+
+    /// Removes a route inserted with [`Router::insert_version_range`] for the given range
+    /// of versions, returning its value.
+    ///
+    /// The route and range must be identical to the ones the value was inserted with,
+    /// including the names of any route parameters. Other ranges of the same route are
+    /// left in place.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert_version_range("/users/{id}", "Old User", 1..=2)?;
+    /// router.insert_version_range("/users/{id}", "User", 3..=5)?;
+    ///
+    /// assert_eq!(router.remove_version_range("/users/{id}", 1..=1), None);
+    /// assert_eq!(router.remove_version_range("/users/{id}", 1..=2), Some("Old User"));
+    /// assert!(router.at("/v1/users/978").is_err());
+    /// assert_eq!(*router.at("/v3/users/978")?.value, "User");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove_version_range(
         &mut self,
+        route: impl Into<String>,
+        versions: RangeInclusive<u32>,
+    ) -> Option<T> {
+        let mut route = route.into();
+        if !route.starts_with('/') {
+            route.insert(0, '/');
+        }
+
+        let ranges = self.version_ranges.route_value_mut(route.clone())?;
+        let i = ranges.iter().position(|(r, _)| *r == versions)?;
+        let (_, value) = ranges.remove(i);
+
+        if ranges.is_empty() {
+            self.version_ranges.remove(route);
+        }
+
+        self.generation += 1;
+        Some(value)
+    }
+
+    // Matches a path against the routes inserted with `insert_version_range`.
+    fn at_version_range<'path>(&self, path: &'path [u8]) -> Option<Found<'_, 'path, T>> {
+        if self.version_ranges.priority == 0 {
+            return None;
+        }
+
+        let rest = path
+            .strip_prefix(b"/")?
+            .strip_prefix(self.version_prefix.as_bytes())?;
+
+        let len = rest.iter().position(|&c| c == b'/').unwrap_or(rest.len());
+        let (digits, rest) = rest.split_at(len);
+
+        // Versions are canonical, so `/v02` does not match version 2.
+        if !digits.iter().all(u8::is_ascii_digit) || digits.starts_with(b"0") && len > 1 {
+            return None;
+        }
+
+        let version = str::from_utf8(digits).ok()?.parse::<u32>().ok()?;
+        let (node, params) = self.version_ranges.search(rest, Vec::new(), &mut ()).ok()?;
+        let ranges = node.value_ref().unwrap();
+        let i = ranges.iter().position(|(r, _)| r.contains(&version))?;
+
+        let version = Param {
+            key: b"version",
+            value: digits,
+        };
+
+        let params = std::iter::once(version).chain(params).collect();
+        Some((Target::Range(node, i), params))
+    }
+
+    /// Tries to find a value among the routes inserted with [`Router::insert_versioned`],
+    /// preferring the highest version less than or equal to `version`.
+    ///
+    /// If the path does not match any route of that version, lower versions are tried in
+    /// turn, so routes are inherited by later versions until they are replaced.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert_versioned(1, "/users/{id}", "v1 User")?;
+    /// router.insert_versioned(1, "/posts/{id}", "v1 Post")?;
+    /// router.insert_versioned(3, "/users/{id}", "v3 User")?;
+    ///
+    /// assert_eq!(*router.at_with_version(2, "/users/1")?.value, "v1 User");
+    /// assert_eq!(*router.at_with_version(4, "/users/1")?.value, "v3 User");
+    /// assert_eq!(*router.at_with_version(4, "/posts/1")?.value, "v1 Post");
+    /// assert!(router.at_with_version(0, "/users/1").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_with_version<'path>(
+        &self,
+        version: u32,
         path: &'path str,
-    ) -> Result<Match<'_, 'path, &mut T>, MatchError> {
-        match self.root.at(path.as_bytes()) {
-            Ok((value, params)) => Ok(Match {
-                // Safety: We have `&mut self`
-                value: unsafe { &mut *value.get() },
-                params,
-            }),
-            Err(e) => Err(e),
+    ) -> Result<Match<'_, 'path, &T>, MatchError> {
+        for root in self.versions.range(..=version).rev().map(|(_, root)| root) {
+            if let Ok((node, params)) = root.search(path.as_bytes(), Vec::new(), &mut ()) {
+                return Ok(Match {
+                    value: node.value_ref().unwrap(),
+                    params,
+                    generation: node.generation,
+                    template_hash: node.template_hash,
+                });
+            }
         }
+
+        Err(MatchError::NotFound)
     }
 
-    pub fn remove(&mut self, path: impl Into<String>) -> Option<T> {
-        self.root.remove(path.into())
+    /// Returns the position of the parameter named `key` among the parameters captured by
+    /// the given route, or `None` if the route or parameter does not exist.
+    ///
+    /// Like [`Router::remove`], the route parameters must match exactly. Resolving the
+    /// index once avoids searching the parameters by key on every match.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{user}/posts/{id}", "A Post")?;
+    ///
+    /// let id = router.param_index("/users/{user}/posts/{id}", "id").unwrap();
+    ///
+    /// let matched = router.at("/users/1/posts/2")?;
+    /// assert_eq!(id.get(&matched.params), Some(&b"2"[..]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn param_index(&self, route: impl Into<String>, key: &str) -> Option<ParamIdx> {
+        self.root
+            .param_index(route.into(), key.as_bytes())
+            .map(ParamIdx)
     }
 
-    #[cfg(feature = "__test_helpers")]
-    pub fn check_priorities(&self) -> Result<u32, (u32, u32)> {
-        self.root.check_priorities()
+    /// Tries to find a value in the router matching the given path, appending the `extra`
+    /// parameters after any route parameters.
+    ///
+    /// This allows injecting parameters that did not come from the path, such as a tenant
+    /// ID. Route parameters come first, so they take precedence when searching by key.
+    pub fn at_with_extra_params<'r, 'path>(
+        &'r self,
+        path: &'path str,
+        extra: impl IntoIterator<Item = Param<'r, 'path>>,
+    ) -> Result<Match<'r, 'path, &'r T>, MatchError> {
+        let mut matched = self.at(path)?;
+        matched.params.extend(extra);
+        Ok(matched)
     }
-}
 
-/// A successful match consisting of the registered value
-/// and URL parameters, returned by [`Router::at`](Router::at).
-#[derive(Debug)]
-pub struct Match<'k, 'v, V> {
-    /// The value stored under the matched node.
-    pub value: V,
+    /// Tries to find a value in the router matching the given path, falling back to the
+    /// value returned by `default_factory` if no route matches.
+    ///
+    /// The matched value is cloned, and `default_factory` is only called on a miss, in
+    /// which case the returned parameters are empty.
+    pub fn at_default<'path, F>(
+        &self,
+        path: &'path str,
+        default_factory: F,
+    ) -> (T, Vec<Param<'_, 'path>>)
+    where
+        T: Clone,
+        F: FnOnce() -> T,
+    {
+        match self.at(path) {
+            Ok(matched) => (matched.value.clone(), matched.params),
+            Err(_) => (default_factory(), Vec::new()),
+        }
+    }
 
-    /// The route parameters. See [parameters](crate#parameters) for more details.
-    pub params: Vec<Param<'k, 'v>>,
-}
+    /// Returns the value matching the given path, without capturing route parameters.
+    ///
+    /// This is faster than [`Router::at`] when the parameters are not needed.
+    pub fn value_at(&self, path: &str) -> Option<&T> {
+        let path = path.as_bytes();
 
-/// A single URL parameter, consisting of a key and a value.
-#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Default, Copy, Clone)]
-pub struct Param<'k, 'v> {
-    pub key: &'k [u8],
-    pub value: &'v [u8],
-}
+        match self.root.search(path, (), &mut ()) {
+            Ok((node, ())) => {
+                self.check_trailing_slash(path, &node.route).ok()?;
+                node.value_ref()
+            }
+            Err(_) => self
+                .lookup_miss(path)
+                .ok()
+                .map(|(target, _)| target.value()),
+        }
+    }
+
+    /// Returns a map from each static route, without any route parameters, to its value.
+    ///
+    /// A static route always matches the path identical to it, so the map can be checked
+    /// before falling back to [`Router::at`] for other paths. Disabled routes are not
+    /// included. The map is a snapshot, and must be rebuilt after the router is modified.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/", "Home")?;
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// let statics = router.compile_static_map();
+    /// let lookup = |path: &str| match statics.get(path) {
+    ///     Some(value) => Some(*value),
+    ///     None => router.at(path).ok().map(|matched| matched.value),
+    /// };
+    ///
+    /// assert_eq!(statics.len(), 1);
+    /// assert_eq!(lookup("/"), Some(&"Home"));
+    /// assert_eq!(lookup("/users/978"), Some(&"A User"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile_static_map(&self) -> HashMap<&str, &T> {
+        let mut map = HashMap::new();
+
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            if let Some(value) = node.value_ref() {
+                if !node.disabled && !node.route.contains('{') {
+                    map.insert(&*node.route, value);
+                }
+            }
+
+            stack.extend(&node.children);
+        }
+
+        map
+    }
+
+    /// Returns the route matching the given path, as it was inserted, without capturing
+    /// route parameters.
+    ///
+    /// This is useful for grouping requests by route, such as for rate limiting or metrics.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// assert_eq!(router.at_pattern("/users/978"), Some("/users/{id}"));
+    /// assert_eq!(router.at_pattern("/posts"), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_pattern(&self, path: &str) -> Option<&str> {
+        let path = path.as_bytes();
+
+        match self.root.search(path, (), &mut ()) {
+            Ok((node, ())) => {
+                self.check_trailing_slash(path, &node.route).ok()?;
+                Some(&node.route)
+            }
+            Err(_) => self
+                .lookup_miss(path)
+                .ok()
+                .map(|(target, _)| target.route()),
+        }
+    }
+
+    /// Tries to find a value in the router matching the given path, passing each route
+    /// parameter to `on_param` and the value to `on_match`.
+    ///
+    /// Unlike [`Router::at`], parameters are buffered on the stack rather than collected
+    /// into a `Vec`, so matching does not allocate unless the search has to backtrack.
+    /// `on_param` is only called for the route that matched, before `on_match`.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// let mut id = None;
+    /// let value = router.at_cb("/users/978", |_key, value| id = Some(value), |value| *value)?;
+    /// assert_eq!(value, "A User");
+    /// assert_eq!(id, Some("978"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_cb<'r, 'path, R>(
+        &'r self,
+        path: &'path str,
+        mut on_param: impl FnMut(&'r str, &'path str),
+        on_match: impl FnOnce(&'r T) -> R,
+    ) -> Result<R, MatchError> {
+        let mut emit = |params: &[Param<'r, 'path>]| {
+            for param in params {
+                // Parameter values are subslices of the path that end at a `/` or the end of
+                // the path, so they are always valid UTF-8.
+                let start = param.value.as_ptr() as usize - path.as_ptr() as usize;
+                let value = &path[start..start + param.value.len()];
+                on_param(std::str::from_utf8(param.key).unwrap(), value);
+            }
+        };
+
+        match self
+            .root
+            .search(path.as_bytes(), ParamBuffer::new(), &mut ())
+        {
+            Ok((node, params)) => {
+                self.check_trailing_slash(path.as_bytes(), &node.route)?;
+                emit(params.as_slice());
+                Ok(on_match(node.value_ref().unwrap()))
+            }
+            Err(_) => {
+                let (target, params) = self.lookup_miss(path.as_bytes())?;
+                emit(&params);
+                Ok(on_match(target.value()))
+            }
+        }
+    }
+
+    /// Tries to find a value in the router matching the given path, checking the route
+    /// parameters with the validator the route was inserted with, if any.
+    ///
+    /// A match is returned even if validation fails, see [`ValidatedMatch::valid`]. Routes
+    /// inserted with [`Router::insert_version_range`] have no validator, so they are always
+    /// valid.
+    ///
+    /// See [`Router::insert_with_validator`] for an example.
+    pub fn at_validated<'path>(
+        &self,
+        path: &'path str,
+    ) -> Result<ValidatedMatch<'_, 'path, &T>, MatchError> {
+        let (target, params) = self.lookup(path.as_bytes())?;
+
+        // Parameter values are subslices of the path that end at a `/` or the end of the
+        // path, so they are always valid UTF-8.
+        let valid = target.validator().map_or(true, |validator| {
+            params
+                .iter()
+                .all(|param| validator(str::from_utf8(param.value).unwrap()))
+        });
+
+        Ok(ValidatedMatch {
+            value: target.value(),
+            params,
+            valid,
+        })
+    }
+
+    /// Returns `true` if any route matches the given path.
+    pub fn contains_path(&self, path: &str) -> bool {
+        self.value_at(path).is_some()
+    }
+
+    /// Returns `true` if some path matches the given route, rather than another route or
+    /// nothing at all.
+    ///
+    /// Route parameters are ignored when comparing routes, as in [`Router::peek_remove`].
+    /// Returns `false` if the route is not registered, has been disabled, or is shadowed by
+    /// its own catch-all exclusions.
+    ///
+    /// Routes are checked by matching a path where every route parameter is replaced by a
+    /// `}`, which [`Router::insert`] rejects outside of a route parameter, so static routes
+    /// can never intercept it.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/{*path}", "Fallback")?;
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.set_enabled("/users/{id}", false);
+    ///
+    /// assert!(router.is_route_reachable("/{*path}"));
+    /// assert!(!router.is_route_reachable("/users/{id}"));
+    /// assert!(!router.is_route_reachable("/posts/{id}"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_route_reachable(&self, route: &str) -> bool {
+        let Some(route) = self.root.find_route(route.to_owned()) else {
+            return false;
+        };
+
+        let mut path = String::with_capacity(route.len());
+        let mut rest = route.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start..].find('}').unwrap();
+            path.push_str(&rest[..start]);
+            path.push('}');
+            rest = &rest[end + 1..];
+        }
+        path.push_str(rest);
+
+        match self.root.search(path.as_bytes(), (), &mut ()) {
+            Ok((node, ())) => *node.route == *route,
+            Err(_) => false,
+        }
+    }
+
+    /// Tries to find a value in the router matching the given path, falling back to the
+    /// route matching the longest prefix of the path that ends before a `/`.
+    ///
+    /// For example, if `/docs/guide/install` does not match any route, `/docs/guide`,
+    /// `/docs`, and `/` are tried in order, so the most specific route matching a given
+    /// prefix is preferred. The unmatched rest of the path is returned in
+    /// [`AncestorMatch::remaining`].
+    ///
+    /// Each prefix is matched exactly as given. The trailing slash behavior does not apply,
+    /// as a path with a trailing slash already falls back to the prefix without it, and
+    /// routes inserted with [`Router::insert_version_range`] are not considered.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/docs/{page}", "A Page")?;
+    ///
+    /// let matched = router.at_or_ancestor("/docs/guide/install/step-3")?;
+    /// assert_eq!(*matched.value, "A Page");
+    /// assert_eq!(matched.params[0].value, b"guide");
+    /// assert_eq!(matched.remaining, "/install/step-3");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_or_ancestor<'path>(
+        &self,
+        path: &'path str,
+    ) -> Result<AncestorMatch<'_, 'path, &T>, MatchError> {
+        self.ancestor(path, &mut ())
+    }
+
+    /// Like [`Router::at_or_ancestor`], but catch-all routes are skipped as if they did not
+    /// exist.
+    ///
+    /// Catch-all routes match every path beneath them, which prevents falling back to a
+    /// more specific ancestor, such as a page registered alongside a `/{*fallback}` route.
+    pub fn at_or_ancestor_without_catch_all<'path>(
+        &self,
+        path: &'path str,
+    ) -> Result<AncestorMatch<'_, 'path, &T>, MatchError> {
+        self.ancestor(path, &mut SkipCatchAll)
+    }
+
+    fn ancestor<'r, 'path>(
+        &'r self,
+        path: &'path str,
+        tracer: &mut impl Tracer<'r, 'path, T>,
+    ) -> Result<AncestorMatch<'r, 'path, &'r T>, MatchError> {
+        // The path itself, followed by every prefix ending before a `/`, longest first.
+        let slashes = path.bytes().enumerate().rev().filter(|&(_, c)| c == b'/');
+        let prefixes = slashes.map(|(i, _)| i.max(1)).filter(|&i| i < path.len());
+
+        for end in std::iter::once(path.len()).chain(prefixes) {
+            let prefix = &path.as_bytes()[..end];
+
+            if let Ok((node, params)) = self.root.search(prefix, Vec::new(), tracer) {
+                return Ok(AncestorMatch {
+                    value: node.value_ref().unwrap(),
+                    params,
+                    remaining: &path[end..],
+                });
+            }
+        }
+
+        Err(MatchError::NotFound)
+    }
+
+    /// Matches each of the given paths, discarding the results.
+    ///
+    /// This touches the nodes along the path to each route, which can be used to bring
+    /// frequently matched routes back into cache after the router has been idle. Paths
+    /// that do not match are ignored.
+    pub fn warm(&self, sample_paths: &[&str]) {
+        for path in sample_paths {
+            let matched = self.root.search(path.as_bytes(), (), &mut ());
+            std::hint::black_box(matched.ok());
+        }
+    }
+
+    /// Tries to find a value in the router matching the given path, returning a mutable
+    /// reference to it.
+    ///
+    /// Paths are matched like [`Router::at`].
+    pub fn at_mut<'path>(
+        &mut self,
+        path: &'path str,
+    ) -> Result<Match<'_, 'path, &mut T>, MatchError> {
+        let (target, params) = self.lookup(path.as_bytes())?;
+
+        // Safety: We have `&mut self`
+        let value = unsafe { target.value_mut() };
+        Ok(target.to_match(value, params))
+    }
+
+    /// Tries to find a value in the router matching the given path, giving up if the
+    /// search takes longer than `budget`.
+    ///
+    /// Paths that match a route directly are found in time proportional to the length of
+    /// the path, so the budget is only checked before the search backtracks to a route
+    /// parameter it skipped earlier, which is where paths against many overlapping routes
+    /// spend their time. Unlike [`Router::at`], the path is matched exactly as given against
+    /// the routes inserted with [`Router::insert`], so versioned routes and the trailing
+    /// slash behavior are not considered.
+    ///
+    /// ```
+    /// use matchit::{MatchError, Router, TimeoutOrMatchError};
+    /// use std::time::Duration;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// let matched = router.at_timeout("/users/978", Duration::from_millis(1))?;
+    /// assert_eq!(*matched.value, "A User");
+    ///
+    /// assert_eq!(
+    ///     router.at_timeout("/posts/1", Duration::from_millis(1)).unwrap_err(),
+    ///     TimeoutOrMatchError::Match(MatchError::NotFound)
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_timeout<'path>(
+        &self,
+        path: &'path str,
+        budget: Duration,
+    ) -> Result<Match<'_, 'path, &T>, TimeoutOrMatchError> {
+        // Stops the search once the deadline has passed.
+        struct Deadline {
+            deadline: Option<Instant>,
+            expired: bool,
+        }
+
+        impl<T> Tracer<'_, '_, T> for Deadline {
+            fn stop(&mut self) -> bool {
+                self.expired = self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline);
+                self.expired
+            }
+        }
+
+        let mut deadline = Deadline {
+            deadline: Instant::now().checked_add(budget),
+            expired: false,
+        };
+
+        match self.root.search(path.as_bytes(), Vec::new(), &mut deadline) {
+            Ok((node, params)) => Ok(Match {
+                value: node.value_ref().unwrap(),
+                params,
+                generation: node.generation,
+                template_hash: node.template_hash,
+            }),
+            Err(_) if deadline.expired => Err(TimeoutOrMatchError::Timeout),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Matches the given path, measuring the time spent searching the router with the
+    /// given clock.
+    ///
+    /// The clock is read once before and once after searching the tree.
+    ///
+    /// ```
+    /// use matchit::{Router, SystemClock};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// let timed = router.at_with_clock("/users/978", &SystemClock)?;
+    /// assert_eq!(*timed.matched.value, "A User");
+    /// println!("matched in {:?}", timed.lookup_duration);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "timing")]
+    pub fn at_with_clock<'path, C: crate::Clock>(
+        &self,
+        path: &'path str,
+        clock: &C,
+    ) -> Result<crate::TimedMatch<'_, 'path, &T>, MatchError> {
+        let start = clock.now();
+        let matched = self.at(path)?;
+        let lookup_duration = clock.now().saturating_duration_since(start);
+
+        Ok(crate::TimedMatch {
+            matched,
+            lookup_duration,
+        })
+    }
+
+    /// Matches the given path, recording each decision made while searching the tree.
+    ///
+    /// This is intended for debugging, and is considerably slower than [`Router::at`]. Only
+    /// the search of the routes inserted with [`Router::insert`] is traced, so versioned
+    /// routes and the trailing slash behavior are not considered.
+    #[cfg(feature = "trace")]
+    pub fn trace_match<'path>(&self, path: &'path str) -> crate::MatchTrace<'path> {
+        let mut trace = crate::MatchTrace::new(path);
+        let result = self.root.search(path.as_bytes(), Vec::new(), &mut trace);
+        trace.matched = result.is_ok();
+        trace
+    }
+
+    /// Matches the given path, also returning the number of node prefixes that were
+    /// compared against the path during the search.
+    ///
+    /// Paths that require a lot of backtracking result in more comparisons, so this can
+    /// be used to find routes that are expensive to match. Like [`Router::trace_match`],
+    /// versioned routes and the trailing slash behavior are not considered.
+    #[cfg(feature = "profile")]
+    pub fn count_match_attempts<'path>(
+        &self,
+        path: &'path str,
+    ) -> (Result<Match<'_, 'path, &T>, MatchError>, usize) {
+        let mut attempts = 0;
+        let result = self
+            .root
+            .search(path.as_bytes(), Vec::new(), &mut attempts)
+            .map(|(node, params)| Match {
+                value: node.value_ref().unwrap(),
+                params,
+                generation: node.generation,
+                template_hash: node.template_hash,
+            });
+
+        (result, attempts)
+    }
+
+    /// Matches the given path, also returning statistics about the search, see
+    /// [`MatchStats`](crate::MatchStats).
+    ///
+    /// Collecting the statistics adds a small overhead to every search. Like
+    /// [`Router::trace_match`], versioned routes and the trailing slash behavior are not
+    /// considered.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.insert("/users/new/form", "New User")?;
+    ///
+    /// let (matched, stats) = router.at_with_stats("/users/new");
+    /// assert_eq!(*matched?.value, "A User");
+    /// assert_eq!(stats.backtracks, 1);
+    /// assert_eq!(stats.param_captures, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn at_with_stats<'path>(
+        &self,
+        path: &'path str,
+    ) -> (Result<Match<'_, 'path, &T>, MatchError>, crate::MatchStats) {
+        let mut stats = crate::MatchStats::default();
+
+        let start = Instant::now();
+        let result = self.root.search(path.as_bytes(), Vec::new(), &mut stats);
+        stats.match_duration_ns = start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+
+        let result = result.map(|(node, params)| Match {
+            value: node.value_ref().unwrap(),
+            params,
+            generation: node.generation,
+            template_hash: node.template_hash,
+        });
+
+        (result, stats)
+    }
+
+    /// Removes the given route from the router, returning its value.
+    ///
+    /// The route must be identical to the one it was inserted with, including the names
+    /// of any route parameters. For example, `/users/{id}` cannot be used to remove
+    /// `/users/{user_id}`.
+    ///
+    /// The router is fully updated before the value is returned, so a panic while dropping
+    /// the value cannot leave the router in an inconsistent state.
+    pub fn remove(&mut self, path: impl Into<String>) -> Option<T> {
+        let path = path.into();
+        let dynamic = path.contains('{');
+
+        match self.observer {
+            Some(ref mut observer) => {
+                let value = self.root.remove(path.clone())?;
+                self.generation += 1;
+                self.dynamic_routes -= usize::from(dynamic);
+
+                // The route must match exactly, so `path` is the route that was removed.
+                observer.on_remove(&path, &value);
+                Some(value)
+            }
+            None => {
+                let value = self.root.remove(path)?;
+                self.generation += 1;
+                self.dynamic_routes -= usize::from(dynamic);
+                Some(value)
+            }
+        }
+    }
+
+    /// Removes the given route from the router, returning its value.
+    ///
+    /// This is an alias for [`Router::remove`], for code that extracts values from the
+    /// router rather than unregistering routes.
+    pub fn take(&mut self, route: impl Into<String>) -> Option<T> {
+        self.remove(route)
+    }
+
+    /// Matches the given path and removes the matched route if `predicate` returns `true`
+    /// for its value, returning the value.
+    ///
+    /// Paths are matched like [`Router::at`]. If the path does not match or the predicate
+    /// returns `false`, the router is unchanged and `None` is returned. Otherwise the route
+    /// is removed as with [`Router::remove`], or [`Router::remove_version_range`] for the
+    /// matched range of versions.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/tokens/abc", 1)?;
+    /// router.insert("/tokens/{token}", 2)?;
+    ///
+    /// assert_eq!(router.at_and_remove_if("/tokens/abc", |&value| value > 1), None);
+    /// assert_eq!(router.at_and_remove_if("/tokens/abc", |&value| value == 1), Some(1));
+    /// assert_eq!(*router.at("/tokens/abc")?.value, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn at_and_remove_if<P>(&mut self, path: &str, predicate: P) -> Option<T>
+    where
+        P: Fn(&T) -> bool,
+    {
+        let (target, _) = self.lookup(path.as_bytes()).ok()?;
+        if !predicate(target.value()) {
+            return None;
+        }
+
+        let route = target.route().to_owned();
+        match target {
+            Target::Node(_) => self.remove(route),
+            Target::Range(node, i) => {
+                let versions = node.value_ref().unwrap()[i].0.clone();
+                self.remove_version_range(route, versions)
+            }
+        }
+    }
+
+    /// Enables or disables matching the given route.
+    ///
+    /// Disabled routes are skipped while matching as if they were not registered, falling
+    /// back to any less specific routes, but remain in the router and can be re-enabled.
+    /// Returns `false` if the route does not exist. Like [`Router::remove`], the names of
+    /// any route parameters must match exactly.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{*rest}", "fallback")?;
+    /// router.insert("/users/new", "new user")?;
+    ///
+    /// router.set_enabled("/users/new", false);
+    /// assert_eq!(*router.at("/users/new")?.value, "fallback");
+    ///
+    /// router.set_enabled("/users/new", true);
+    /// assert_eq!(*router.at("/users/new")?.value, "new user");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_enabled(&mut self, route: impl Into<String>, enabled: bool) -> bool {
+        self.root.set_enabled(route.into(), enabled)
+    }
+
+    /// Inserts a catch-all route that does not match any path under the given prefixes.
+    ///
+    /// Excluded paths are matched as if the catch-all route did not exist, so they fall
+    /// back to any less specific routes, or fail to match entirely. Prefixes are compared
+    /// against the full path and only match entire segments, so `/api` excludes `/api` and
+    /// `/api/users`, but not `/apiary`.
+    ///
+    /// Returns [`InsertError::InvalidCatchAll`] if the route does not end with a catch-all
+    /// parameter.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/api/users/{id}", "A User")?;
+    /// router.insert_catch_all_with_exclusions("/{*spa}", "App", &["/api", "/assets"])?;
+    ///
+    /// assert_eq!(*router.at("/about")?.value, "App");
+    /// assert_eq!(*router.at("/api/users/978")?.value, "A User");
+    /// assert!(router.at("/api/unknown").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert_catch_all_with_exclusions(
+        &mut self,
+        route: impl Into<String>,
+        value: T,
+        exclusions: &[&str],
+    ) -> Result<(), InsertError> {
+        let route = route.into();
+
+        let catch_all = route.rfind('{').map(|i| &route[i..]);
+        if !matches!(catch_all, Some(param) if param.starts_with("{*") && param.ends_with('}')) {
+            return Err(InsertError::InvalidCatchAll);
+        }
+
+        self.insert(route.clone(), value)?;
+
+        let exclusions = exclusions.iter().map(|prefix| prefix.as_bytes().to_vec());
+        self.root.set_exclusions(route, exclusions.collect());
+        Ok(())
+    }
+
+    /// Inserts a route into the router along with a validator for its route parameters.
+    ///
+    /// The validator is called with the value of each route parameter when the route is
+    /// matched by [`Router::at_validated`], which reports whether every value was accepted.
+    /// Other methods match the route without calling the validator.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert_with_validator("/users/{id}", "A User", |id| id.parse::<u32>().is_ok())?;
+    ///
+    /// assert!(router.at_validated("/users/978")?.valid);
+    /// assert!(!router.at_validated("/users/me")?.valid);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert_with_validator<F>(
+        &mut self,
+        route: impl Into<String>,
+        value: T,
+        validator: F,
+    ) -> Result<(), InsertError>
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        let route = route.into();
+        self.insert(route.clone(), value)?;
+        self.root.set_validator(route, Arc::new(validator));
+        Ok(())
+    }
+
+    /// Returns the registered route with the same shape as the given route, regardless of
+    /// the names of any route parameters.
+    ///
+    /// This allows confirming which route is registered before calling [`Router::remove`],
+    /// which requires the parameter names to match exactly.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{user_id}", "A User")?;
+    ///
+    /// assert_eq!(router.peek_remove("/users/{id}").as_deref(), Some("/users/{user_id}"));
+    /// assert_eq!(router.remove("/users/{id}"), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn peek_remove(&self, route: impl Into<String>) -> Option<String> {
+        self.root.find_route(route.into())
+    }
+
+    /// Removes the given prefix from every route in the router, returning the number of
+    /// routes that were modified.
+    ///
+    /// This is useful for mounting a router under a prefix, such as `/api/v1`. A route equal
+    /// to the prefix becomes `/`. If any route does not start with the prefix, would no
+    /// longer start with a `/`, or could not be inserted into this router after stripping
+    /// it, the router is left unchanged. Routes keep the generation they were inserted at,
+    /// whether they are enabled, and any excluded prefixes, which are stripped as well.
+    ///
+    /// ```
+    /// use matchit::{Router, StripError};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/api/v1/users", "Users")?;
+    /// router.insert("/api/v1/users/{id}", "A User")?;
+    ///
+    /// assert_eq!(router.strip_prefix_from_all("/api/v1")?, 2);
+    /// assert_eq!(*router.at("/users/978")?.value, "A User");
+    ///
+    /// router.insert("/health", "Health")?;
+    /// assert_eq!(
+    ///     router.strip_prefix_from_all("/users"),
+    ///     Err(StripError::NotUniversal { route: "/health".to_owned() })
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn strip_prefix_from_all(&mut self, prefix: &str) -> Result<usize, StripError> {
+        let routes = (0..self.len())
+            .map(|n| {
+                let (route, node) = self.root.nth_route(n).unwrap();
+                (
+                    String::from_utf8(route).unwrap(),
+                    node.generation,
+                    node.disabled,
+                    node.exclusions.clone(),
+                    node.validator.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if let Some((route, ..)) = routes.iter().find(|(route, ..)| !route.starts_with(prefix)) {
+            return Err(StripError::NotUniversal {
+                route: route.clone(),
+            });
+        }
+
+        // A route equal to the prefix is mounted at the root.
+        let strip = |route: &str| match &route[prefix.len()..] {
+            "" => "/".to_owned(),
+            stripped => stripped.to_owned(),
+        };
+
+        // Make sure all stripped routes can be inserted before modifying the router.
+        let mut stripped = Node::default();
+        for (route, ..) in &routes {
+            let route_stripped = strip(route);
+            if !route_stripped.starts_with('/') {
+                return Err(StripError::MissingLeadingSlash {
+                    route: route.clone(),
+                });
+            }
+
+            self.validate(&route_stripped).map_err(StripError::Insert)?;
+            stripped
+                .insert(route_stripped, ())
+                .map_err(StripError::Insert)?;
+        }
+
+        let mut root = mem::take(&mut self.root);
+        for (route, generation, disabled, exclusions, validator) in &routes {
+            let value = root.remove(route.clone()).unwrap();
+            let stripped = strip(route);
+
+            self.root
+                .insert_with_generation(stripped.clone(), value, *generation)
+                .unwrap();
+
+            if *disabled {
+                self.root.set_enabled(stripped.clone(), false);
+            }
+
+            if let Some(validator) = validator {
+                self.root.set_validator(stripped.clone(), validator.clone());
+            }
+
+            // Exclusions are compared against the full path, so they are stripped as well.
+            if !exclusions.is_empty() {
+                let exclusions = exclusions.iter().map(|exclusion| {
+                    let stripped = exclusion.strip_prefix(prefix.as_bytes());
+                    stripped.unwrap_or(exclusion).to_vec()
+                });
+
+                self.root.set_exclusions(stripped, exclusions.collect());
+            }
+        }
+
+        if let Some(ref mut observer) = self.observer {
+            for (route, ..) in &routes {
+                let stripped = strip(route);
+                let value = self.root.route_value(stripped.clone()).unwrap();
+                observer.on_remove(route, value);
+                observer.on_insert(&stripped, value);
+            }
+        }
+
+        if !routes.is_empty() {
+            self.generation += 1;
+        }
+
+        // The prefix may have contained route parameters.
+        self.dynamic_routes = routes
+            .iter()
+            .filter(|(route, ..)| strip(route).contains('{'))
+            .count();
+
+        Ok(routes.len())
+    }
+
+    /// Performs a depth-first traversal of the underlying tree with the given visitor.
+    ///
+    /// ```
+    /// use matchit::{Router, TreeVisitor};
+    ///
+    /// // Counts the number of route parameter nodes.
+    /// struct CountParams(usize);
+    ///
+    /// impl<T> TreeVisitor<T> for CountParams {
+    ///     fn visit_dynamic_node(&mut self, _prefix: &[u8], _depth: usize) {
+    ///         self.0 += 1;
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.insert("/users/{id}/posts/{post}", "A Post")?;
+    ///
+    /// let mut visitor = CountParams(0);
+    /// router.visit(&mut visitor);
+    /// assert_eq!(visitor.0, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn visit<V: TreeVisitor<T>>(&self, visitor: &mut V) {
+        self.root.visit(visitor);
+    }
+
+    /// Returns the routes in this router as an [OpenAPI paths object].
+    ///
+    /// Route parameters are listed as required path parameters with the default `simple`
+    /// style. Catch-all parameters such as `{*path}` are converted to `{path}`, but OpenAPI
+    /// has no way to describe a path parameter spanning multiple segments, so the `/`
+    /// separators in a catch-all value are not percent-encoded as the `simple` style would
+    /// require.
+    ///
+    /// [OpenAPI paths object]: https://spec.openapis.org/oas/v3.1.0#paths-object
+    #[cfg(feature = "openapi")]
+    pub fn to_openapi_paths(&self) -> serde_json::Value {
+        let mut paths = serde_json::Map::new();
+
+        for (route, _) in self.routes() {
+            let mut path = String::new();
+            let mut parameters = Vec::new();
+
+            let mut rest = route.as_str();
+            while let Some(start) = rest.find('{') {
+                let end = start + rest[start..].find('}').unwrap();
+                path.push_str(&rest[..start]);
+
+                let name = &rest[start + 1..end];
+                let name = name.strip_prefix('*').unwrap_or(name);
+
+                parameters.push(serde_json::json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }));
+                path.push('{');
+                path.push_str(name);
+                path.push('}');
+                rest = &rest[end + 1..];
+            }
+
+            path.push_str(rest);
+
+            let item = if parameters.is_empty() {
+                serde_json::json!({})
+            } else {
+                serde_json::json!({ "parameters": parameters })
+            };
+
+            paths.insert(path, item);
+        }
+
+        serde_json::Value::Object(paths)
+    }
+
+    /// Returns an iterator over the routes in the router along with their hashes, as
+    /// returned by [`Match::template_hash`].
+    pub fn template_hashes(&self) -> impl Iterator<Item = (u64, String)> + '_ {
+        (0..self.len()).map(move |n| {
+            let (route, node) = self.root.nth_route(n).unwrap();
+            (node.template_hash, String::from_utf8(route).unwrap())
+        })
+    }
+
+    /// Returns the routes that match at least one path starting with the given prefix, in
+    /// sorted order.
+    ///
+    /// This is useful for suggesting routes as a path is being typed.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.insert("/users/new", "New User")?;
+    /// router.insert("/posts", "Posts")?;
+    ///
+    /// let routes = router.paths_matching_prefix("/users/4").collect::<Vec<_>>();
+    /// assert_eq!(routes, ["/users/{id}"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paths_matching_prefix<'a>(
+        &'a self,
+        path_prefix: &'a str,
+    ) -> impl Iterator<Item = String> + 'a {
+        self.routes().map(|(route, _)| route).filter(move |route| {
+            crate::tree::route_matches_prefix(route.as_bytes(), path_prefix.as_bytes())
+        })
+    }
+
+    /// Returns the given path with its segments replaced by route templates, such that
+    /// no part of the path that was not matched by a route is included.
+    ///
+    /// This is intended for recording paths where they may contain sensitive information,
+    /// such as in logs. If the path matches a route, the route is returned. Otherwise, the
+    /// path is generalized as follows:
+    ///
+    /// 1. Paths are split into segments at every `/`.
+    /// 2. A route matches a segment if the segment of the route is identical, or is a
+    ///    route parameter, or a static prefix followed by a route parameter, that matches a
+    ///    non-empty value. Catch-all parameters do not match any segments.
+    /// 3. The leading segments are replaced by the corresponding segments of the route
+    ///    that matches the most leading segments. Ties are broken by preferring the route
+    ///    with the most identical segments, followed by the segments that sort first.
+    /// 4. Any remaining non-empty segments are replaced by `*`, unless there are more than
+    ///    [`Redacted::MAX_PLACEHOLDERS`] remaining segments, in which case they are all
+    ///    replaced by a single `**`.
+    ///
+    /// The length of the result is therefore at most the length of the longest route plus
+    /// `2 * Redacted::MAX_PLACEHOLDERS` bytes, one `/*` for each placeholder, regardless of
+    /// the length of the path. Generalizing a path visits every route in the router.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.insert("/users/{id}/posts", "Posts")?;
+    ///
+    /// assert_eq!(router.redact("/users/alice").as_str(), "/users/{id}");
+    /// assert_eq!(router.redact("/users/alice/secret/1").as_str(), "/users/{id}/*/*");
+    /// assert_eq!(router.redact("/private/alice").as_str(), "/*/*");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn redact(&self, path: &str) -> Redacted<'_> {
+        if let Some(route) = self.at_pattern(path) {
+            return Redacted::Matched(route);
+        }
+
+        let segments = path.split('/').collect::<Vec<_>>();
+
+        // The prefix of the route that matches the most segments, ranked by the number of
+        // matching segments and identical segments.
+        let mut best = None;
+
+        for (route, _) in self.routes() {
+            let mut prefix = Vec::new();
+            let mut identical = 0;
+
+            for (template, segment) in route.split('/').zip(&segments) {
+                match template.find('{') {
+                    None if template == *segment => identical += 1,
+                    Some(i)
+                        if !template[i..].starts_with("{*")
+                            && segment.len() > i
+                            && segment.starts_with(&template[..i]) => {}
+                    _ => break,
+                }
+
+                prefix.push(template.to_owned());
+            }
+
+            best = best.max(Some((prefix.len(), identical, Reverse(prefix))));
+        }
+
+        let mut redacted = best
+            .map(|(_, _, Reverse(prefix))| prefix)
+            .unwrap_or_default();
+        let remaining = &segments[redacted.len()..];
+
+        if remaining.len() > Redacted::MAX_PLACEHOLDERS {
+            redacted.push("**".to_owned());
+        } else {
+            let placeholder = |segment: &&str| if segment.is_empty() { "" } else { "*" };
+            redacted.extend(remaining.iter().map(placeholder).map(str::to_owned));
+        }
+
+        Redacted::Generalized(redacted.join("/"))
+    }
+
+    /// Returns `true` if any two catch-all routes can match the same path.
+    ///
+    /// For example, `/files/{*path}` and `/{dir}/{*path}` both match `/files/a`. The more
+    /// specific route is preferred when matching, but such overlaps can be surprising.
+    pub fn has_overlapping_wildcards(&self) -> bool {
+        let catch_alls = self
+            .routes()
+            .map(|(route, _)| route)
+            .filter(|route| route.contains("{*"))
+            .collect::<Vec<_>>();
+
+        catch_alls.iter().enumerate().any(|(i, a)| {
+            catch_alls[i + 1..]
+                .iter()
+                .any(|b| crate::tree::routes_overlap(a.as_bytes(), b.as_bytes()))
+        })
+    }
+
+    /// Returns the current generation of the router.
+    ///
+    /// The generation starts at zero and is incremented by every successful insertion
+    /// or removal. Each route records the generation it was inserted at, see
+    /// [`Match::generation`] and [`Router::iter_with_generation`].
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns an iterator over the routes in the router, their values, and the
+    /// generation each route was inserted at.
+    ///
+    /// Routes are yielded in the same order as [`Router::routes`].
+    pub fn iter_with_generation(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (String, &T, u64)> + ExactSizeIterator + '_ {
+        (0..self.len()).map(move |n| {
+            let (route, node) = self.root.nth_route(n).unwrap();
+            (
+                String::from_utf8(route).unwrap(),
+                node.value_ref().unwrap(),
+                node.generation,
+            )
+        })
+    }
+
+    /// Returns aggregate statistics about the routes in the router, computed in a single
+    /// traversal of the tree.
+    ///
+    /// This takes time proportional to the size of the router, and is intended for
+    /// diagnostics rather than to be called on every request. Like [`Router::len`], only
+    /// routes inserted with [`Router::insert`] are included.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/api/users", "Users")?;
+    /// router.insert("/api/users/{id}", "A User")?;
+    /// router.insert("/api/static/{*path}", "Static")?;
+    ///
+    /// let summary = router.summary();
+    /// assert_eq!(summary.total_routes, 3);
+    /// assert_eq!(summary.static_routes, 1);
+    /// assert_eq!(summary.dynamic_routes, 1);
+    /// assert_eq!(summary.wildcard_routes, 1);
+    /// assert_eq!(summary.common_prefix.as_deref(), Some("/api/"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn summary(&self) -> RouterSummary {
+        struct Summarize(RouterSummary);
+
+        impl<T> TreeVisitor<T> for Summarize {
+            fn visit_static_node(&mut self, _prefix: &[u8], depth: usize) {
+                self.0.total_nodes += 1;
+                self.0.max_depth = self.0.max_depth.max(depth);
+            }
+
+            fn visit_dynamic_node(&mut self, _prefix: &[u8], depth: usize) {
+                self.0.total_nodes += 1;
+                self.0.max_depth = self.0.max_depth.max(depth);
+            }
+
+            fn visit_wildcard_node(&mut self, _prefix: &[u8], depth: usize) {
+                self.0.total_nodes += 1;
+                self.0.max_depth = self.0.max_depth.max(depth);
+            }
+
+            fn visit_value(&mut self, route: &str, _value: &T) {
+                let summary = &mut self.0;
+                summary.total_routes += 1;
+
+                if route.contains("{*") {
+                    summary.wildcard_routes += 1;
+                } else if route.contains('{') {
+                    summary.dynamic_routes += 1;
+                } else {
+                    summary.static_routes += 1;
+                }
+
+                match summary.common_prefix {
+                    Some(ref mut prefix) => {
+                        let mut len = prefix
+                            .bytes()
+                            .zip(route.bytes())
+                            .take_while(|(a, b)| a == b)
+                            .count();
+
+                        while !route.is_char_boundary(len) {
+                            len -= 1;
+                        }
+
+                        prefix.truncate(len);
+                    }
+                    None => summary.common_prefix = Some(route.to_owned()),
+                }
+            }
+        }
+
+        if self.is_empty() {
+            return RouterSummary::default();
+        }
+
+        let mut visitor = Summarize(RouterSummary::default());
+        self.visit(&mut visitor);
+
+        let mut summary = visitor.0;
+        summary.common_prefix = summary.common_prefix.filter(|prefix| !prefix.is_empty());
+        summary
+    }
+
+    /// Returns a table of the nodes in the router, one per line, for logging.
+    ///
+    /// Each row shows the route leading to the node, whether a value is stored at the node,
+    /// the type of the node, and its depth in the tree. Nodes are listed in the order they
+    /// are searched, parents before their children.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/", "Home")?;
+    /// router.insert("/users/{id}", "A User")?;
+    ///
+    /// assert_eq!(
+    ///     router.format_as_table(),
+    ///     "\
+    /// | Route Pattern | Has Value | Node Type | Depth |
+    /// | ------------- | --------- | --------- | ----- |
+    /// | /             | yes       | root      | 0     |
+    /// | /users/       | no        | static    | 1     |
+    /// | /users/{id}   | yes       | param     | 2     |
+    /// "
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn format_as_table(&self) -> String {
+        const HEADER: [&str; 4] = ["Route Pattern", "Has Value", "Node Type", "Depth"];
+
+        let mut rows = Vec::new();
+
+        // An empty router only has an empty root node, which is not listed.
+        let root = &self.root;
+        let empty = root.prefix.is_empty() && root.children.is_empty() && root.value.is_none();
+        let mut stack = if empty {
+            Vec::new()
+        } else {
+            vec![(root, 0, Vec::new())]
+        };
+
+        while let Some((node, depth, mut route)) = stack.pop() {
+            route.extend_from_slice(&node.prefix);
+            let pattern = node_pattern(node, &route);
+
+            let node_type = match node.node_type {
+                NodeType::Root => "root",
+                NodeType::Static => "static",
+                NodeType::Param => "param",
+                NodeType::CatchAll => "catch-all",
+            };
+
+            let has_value = if node.value.is_some() { "yes" } else { "no" };
+            rows.push([
+                pattern,
+                has_value.to_owned(),
+                node_type.to_owned(),
+                depth.to_string(),
+            ]);
+
+            for child in node.children.iter().rev() {
+                stack.push((child, depth + 1, route.clone()));
+            }
+        }
+
+        let mut widths = HEADER.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let line = |table: &mut String, cells: [&str; 4]| {
+            for (cell, width) in cells.iter().zip(widths) {
+                let _ = write!(table, "| {cell:width$} ");
+            }
+            table.push_str("|\n");
+        };
+
+        let mut table = String::new();
+        line(&mut table, HEADER);
+        line(
+            &mut table,
+            widths
+                .map(|width| "-".repeat(width))
+                .each_ref()
+                .map(String::as_str),
+        );
+        for row in &rows {
+            line(&mut table, row.each_ref().map(String::as_str));
+        }
+
+        table
+    }
+
+    /// Returns the number of routes beneath each node in the router, keyed by the route
+    /// pattern leading to the node.
+    ///
+    /// Prefixes shared by many routes are stored once, so this shows how well the router
+    /// compresses its routes. A count of one marks a prefix that is not shared by any other
+    /// route. Nodes left behind by removed routes have a count of zero. An empty router
+    /// returns an empty map.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.insert("/users/new", "New User")?;
+    ///
+    /// let counts = router.count_shared_prefixes();
+    /// assert_eq!(counts["/users/"], 2);
+    /// assert_eq!(counts["/users/{id}"], 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn count_shared_prefixes(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        if self.root.prefix.is_empty() && self.root.children.is_empty() {
+            return counts;
+        }
+
+        let mut stack = vec![(&self.root, Vec::new())];
+        while let Some((node, mut route)) = stack.pop() {
+            route.extend_from_slice(&node.prefix);
+            counts.insert(node_pattern(node, &route), node.priority as usize);
+
+            for child in &node.children {
+                stack.push((child, route.clone()));
+            }
+        }
+
+        counts
+    }
+
+    /// Returns the number of routes in the router.
+    ///
+    /// Routes inserted with [`Router::insert_versioned`] or [`Router::insert_version_range`]
+    /// are not counted.
+    pub fn len(&self) -> usize {
+        self.root.priority as usize
+    }
+
+    /// Returns `true` if the router does not contain any routes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if any route contains a route parameter or catch-all parameter.
+    ///
+    /// This is tracked as routes are inserted and removed, so it does not traverse the
+    /// router. Versioned routes are not included.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users", "Users")?;
+    /// assert!(!router.has_dynamic_routes());
+    ///
+    /// router.insert("/users/{id}", "A User")?;
+    /// assert!(router.has_dynamic_routes());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn has_dynamic_routes(&self) -> bool {
+        self.dynamic_routes > 0
+    }
+
+    /// Returns `true` if no route contains a route parameter or catch-all parameter,
+    /// including if the router is empty.
+    ///
+    /// If all routes are static, paths can be matched by a simple lookup instead, see
+    /// [`Router::compile_static_map`].
+    pub fn all_routes_static(&self) -> bool {
+        !self.has_dynamic_routes()
+    }
+
+    /// Returns the route at the given index and its value, in the same order as
+    /// [`Router::routes`], or `None` if the index is out of bounds.
+    ///
+    /// The route is found using the number of routes beneath each node, without visiting
+    /// the routes before it. Together with [`Router::len`], this can be used to select a
+    /// route uniformly at random, for example to generate paths in property-based tests.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// use std::collections::hash_map::RandomState;
+    /// use std::hash::BuildHasher;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", "A User")?;
+    /// router.insert("/posts/{id}", "A Post")?;
+    ///
+    /// let random = RandomState::new().hash_one(()) as usize;
+    /// let (route, value) = router.nth_route(random % router.len()).unwrap();
+    /// assert_eq!(*router.at(&route.replace("{id}", "1"))?.value, *value);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn nth_route(&self, n: usize) -> Option<(&str, &T)> {
+        let (_, node) = self.root.nth_route(n)?;
+        Some((&node.route, node.value_ref()?))
+    }
+
+    /// Returns an iterator over the routes in the router and their values.
+    ///
+    /// Routes are yielded in lexicographic order, with route parameters compared by
+    /// position rather than by name. Routes inserted with [`Router::insert_versioned`] or
+    /// [`Router::insert_version_range`] are not included, see [`Router::version_ranges`].
+    pub fn routes(&self) -> Routes<'_, T> {
+        Routes {
+            root: &self.root,
+            front: 0,
+            back: self.len(),
+        }
+    }
+
+    #[cfg(feature = "__test_helpers")]
+    pub fn check_priorities(&self) -> Result<u32, (u32, u32)> {
+        self.root.check_priorities()
+    }
+
+    #[cfg(feature = "__test_helpers")]
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.root.check_invariants()
+    }
+}
+
+impl<T> Router<Lazy<T>> {
+    /// Inserts a route whose value is built by `init` the first time the route is
+    /// matched with [`Router::at_lazy`].
+    ///
+    /// The value is built at most once, even if the route is matched from multiple
+    /// threads at the same time. Other methods see the [`Lazy`] wrapper, so values that
+    /// have not been built can be told apart from those that have:
+    ///
+    /// - [`Router::at_mut`] returns the wrapper without building the value, see
+    ///   [`Lazy::force_mut`] to build it.
+    /// - [`Router::remove`] returns the wrapper, and [`Lazy::into_inner`] returns the value
+    ///   only if it was built, dropping the closure otherwise.
+    /// - [`Router::initialized`] iterates over the values that have been built.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert_lazy("/users/{id}", || "A User".to_owned())?;
+    /// assert_eq!(router.initialized().count(), 0);
+    ///
+    /// assert_eq!(router.at_lazy("/users/978")?.value, "A User");
+    /// assert_eq!(router.initialized().count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert_lazy(
+        &mut self,
+        route: impl Into<String>,
+        init: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<(), InsertError> {
+        self.insert(route, Lazy::new(init))
+    }
+
+    /// Tries to find a value in the router matching the given path, building the value if
+    /// this is the first time it was matched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if building the matched value panicked on a previous match.
+    pub fn at_lazy<'path>(&self, path: &'path str) -> Result<Match<'_, 'path, &T>, MatchError> {
+        let matched = self.at(path)?;
+
+        Ok(Match {
+            value: matched.value.force(),
+            params: matched.params,
+            generation: matched.generation,
+            template_hash: matched.template_hash,
+        })
+    }
+
+    /// Returns an iterator over the routes whose values have been built, in the same
+    /// order as [`Router::routes`].
+    pub fn initialized(&self) -> impl Iterator<Item = (String, &T)> {
+        self.routes()
+            .filter_map(|(route, value)| Some((route, value.get()?)))
+    }
+}
+
+/// Aggregate statistics about the routes in a router, returned by
+/// [`Router::summary`](Router::summary).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RouterSummary {
+    /// The number of routes.
+    pub total_routes: usize,
+    /// The number of routes without any parameters.
+    pub static_routes: usize,
+    /// The number of routes with route parameters, but no catch-all parameter.
+    pub dynamic_routes: usize,
+    /// The number of routes ending with a catch-all parameter.
+    pub wildcard_routes: usize,
+    /// The largest number of ancestors of any node in the tree.
+    pub max_depth: usize,
+    /// The number of nodes in the tree.
+    pub total_nodes: usize,
+    /// The longest prefix shared by all routes, or `None` if there is no such prefix.
+    pub common_prefix: Option<String>,
+}
+
+/// A successful match consisting of the registered value
+/// and URL parameters, returned by [`Router::at`](Router::at).
+///
+/// # Lifetimes
+///
+/// A match borrows parameter keys from the router (`'router`) and parameter values
+/// from the matched path (`'path`), so it cannot outlive either of them. In particular,
+/// a path created with `format!` must be stored in a variable that lives at least as
+/// long as the match:
+///
+/// ```compile_fail,E0716
+/// # use matchit::Router;
+/// # let mut router = Router::new();
+/// # router.insert("/users/{id}", "A User").unwrap();
+/// # let id = 978;
+/// // error: temporary value dropped while borrowed
+/// let matched = router.at(&format!("/users/{id}")).unwrap();
+/// println!("{:?}", matched.params);
+/// ```
+///
+/// ```
+/// # use matchit::Router;
+/// # let mut router = Router::new();
+/// # router.insert("/users/{id}", "A User").unwrap();
+/// # let id = 978;
+/// let path = format!("/users/{id}");
+/// let matched = router.at(&path).unwrap();
+/// println!("{:?}", matched.params);
+/// ```
+///
+/// Likewise, a match cannot outlive the router it came from:
+///
+/// ```compile_fail,E0597
+/// # use matchit::Router;
+/// let params = {
+///     let mut router = Router::new();
+///     router.insert("/users/{id}", "A User").unwrap();
+///     // error: `router` does not live long enough
+///     router.at("/users/978").unwrap().params
+/// };
+/// ```
+///
+/// Rather than cloning the router or path, copy the parameters into owned values if
+/// they need to be kept around:
+///
+/// ```
+/// # use matchit::Router;
+/// let params = {
+///     let mut router = Router::new();
+///     router.insert("/users/{id}", "A User").unwrap();
+///     let matched = router.at("/users/978").unwrap();
+///     matched
+///         .params
+///         .iter()
+///         .map(|param| (param.key.to_vec(), param.value.to_vec()))
+///         .collect::<Vec<_>>()
+/// };
+///
+/// assert_eq!(params, [(b"id".to_vec(), b"978".to_vec())]);
+/// ```
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct Match<'router, 'path, V> {
+    /// The value stored under the matched node.
+    pub value: V,
+
+    /// The route parameters. See [parameters](crate#parameters) for more details.
+    pub params: Vec<Param<'router, 'path>>,
+
+    /// The router generation at which the matched route was inserted.
+    ///
+    /// See [`Router::generation`] for details.
+    pub generation: u64,
+
+    // A hash of the matched route.
+    template_hash: u64,
+}
+
+impl<V> Match<'_, '_, V> {
+    /// Returns a stable hash of the matched route, as inserted into the router.
+    ///
+    /// Unlike paths, the set of routes is bounded, making the hash suitable for labeling
+    /// metrics. Hashes can be resolved back to routes with [`Router::template_hashes`],
+    /// see [`template_hash`](crate::tree::template_hash) for details about stability.
+    pub fn template_hash(&self) -> u64 {
+        self.template_hash
+    }
+}
+
+// A route matched by `Router::lookup`, along with its route parameters.
+type Found<'r, 'path, T> = (Target<'r, T>, Vec<Param<'r, 'path>>);
+
+// A route matched by `Router::lookup`.
+enum Target<'r, T> {
+    // A route inserted with `Router::insert`.
+    Node(&'r Node<T>),
+    // A route inserted with `Router::insert_version_range`, along with the index of the
+    // matched range.
+    Range(&'r Node<Vec<(RangeInclusive<u32>, T)>>, usize),
+}
+
+impl<'r, T> Target<'r, T> {
+    fn value(&self) -> &'r T {
+        match *self {
+            Target::Node(node) => node.value_ref().unwrap(),
+            Target::Range(node, i) => &node.value_ref().unwrap()[i].1,
+        }
+    }
+
+    // Safety: The caller must have exclusive access to the router.
+    unsafe fn value_mut(&self) -> &'r mut T {
+        match *self {
+            Target::Node(node) => &mut *node.value.as_ref().unwrap().get(),
+            Target::Range(node, i) => {
+                let ranges = &mut *node.value.as_ref().unwrap().get();
+                &mut ranges[i].1
+            }
+        }
+    }
+
+    fn route(&self) -> &'r str {
+        match *self {
+            Target::Node(node) => &node.route,
+            Target::Range(node, _) => &node.route,
+        }
+    }
+
+    fn validator(&self) -> Option<&'r tree::Validator> {
+        match *self {
+            Target::Node(node) => node.validator.as_ref(),
+            Target::Range(..) => None,
+        }
+    }
+
+    fn to_match<'path, V>(&self, value: V, params: Vec<Param<'r, 'path>>) -> Match<'r, 'path, V> {
+        let (generation, template_hash) = match *self {
+            Target::Node(node) => (node.generation, node.template_hash),
+            Target::Range(node, _) => (node.generation, node.template_hash),
+        };
+
+        Match {
+            value,
+            params,
+            generation,
+            template_hash,
+        }
+    }
+}
+
+// Decodes the encoded slashes in a route parameter value.
+fn decode_slashes(value: &str) -> Cow<'_, str> {
+    if !value.contains("%2F") && !value.contains("%2f") {
+        return Cow::Borrowed(value);
+    }
+
+    Cow::Owned(value.replace("%2F", "/").replace("%2f", "/"))
+}
+
+// Returns the route pattern of the given node, where `route` is the normalized path to
+// the node, including its prefix.
+fn node_pattern<T>(node: &Node<T>, route: &[u8]) -> String {
+    if node.value.is_some() {
+        return node.route.to_string();
+    }
+
+    // Parameter names are only stored with values, so use the names of the first route
+    // beneath this node.
+    let mut leaf = node;
+    while let (None, Some(child)) = (&leaf.value, leaf.children.first()) {
+        leaf = child;
+    }
+
+    let mut route = route.to_owned();
+    tree::denormalize_params(&mut route, &leaf.remapping);
+    String::from_utf8_lossy(&route).into_owned()
+}
+
+/// A match of a path or one of its prefixes, returned by
+/// [`Router::at_or_ancestor`](Router::at_or_ancestor).
+#[derive(Debug)]
+pub struct AncestorMatch<'router, 'path, V> {
+    /// The value stored under the matched node.
+    pub value: V,
+
+    /// The route parameters captured from the matched prefix.
+    pub params: Vec<Param<'router, 'path>>,
+
+    /// The rest of the path after the matched prefix, which is empty if the entire path
+    /// matched.
+    pub remaining: &'path str,
+}
+
+/// A match whose route parameters were checked by a validator, returned by
+/// [`Router::at_validated`].
+#[derive(Debug)]
+pub struct ValidatedMatch<'router, 'path, V> {
+    /// The value stored under the matched node.
+    pub value: V,
+
+    /// The route parameters captured from the path.
+    pub params: Vec<Param<'router, 'path>>,
+
+    /// Whether the validator accepted every route parameter, or `true` if the route was
+    /// inserted without a validator.
+    pub valid: bool,
+}
+
+/// A match whose route parameter values had their encoded slashes decoded, returned by
+/// [`Router::at_encoded_slash`].
+#[derive(Debug)]
+pub struct DecodedMatch<'router, 'path, V> {
+    /// The match, with route parameters borrowed from the path as given.
+    pub matched: Match<'router, 'path, V>,
+
+    /// The value of each route parameter in the same order as [`Match::params`], with
+    /// encoded slashes decoded.
+    pub values: Vec<Cow<'path, str>>,
+}
+
+/// How a router handles a path that only differs from a route by a trailing slash, see
+/// [`Router::with_trailing_slash_behavior`].
+///
+/// The root path `/` is never considered to have a trailing slash.
+///
+/// For `Redirect`, the path to redirect to is returned by [`MatchError::redirect_target`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TrailingSlashBehavior {
+    /// Paths are matched exactly, so `/users/` does not match `/users`. Catch-all
+    /// parameters capture any trailing slash.
+    #[default]
+    Strict,
+    /// Like `Strict`, but a path that would match with its trailing slash removed or added
+    /// returns [`MatchError::ExtraTrailingSlash`] or [`MatchError::MissingTrailingSlash`],
+    /// so that the caller can redirect to the path that matches.
+    Redirect,
+    /// A path that does not match is matched again with its trailing slash removed or
+    /// added, so `/users/` matches `/users` and the other way around.
+    Accept,
+    /// Like `Strict`, but a path with a trailing slash only matches a route that ends with
+    /// a slash, so `/static/a/` does not match `/static/{*path}`.
+    Reject,
+}
+
+/// A path with any raw segments replaced by route templates or placeholders, returned by
+/// [`Router::redact`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Redacted<'router> {
+    /// The path matched the given route.
+    Matched(&'router str),
+    /// The path did not match any route, and was generalized.
+    Generalized(String),
+}
+
+impl Redacted<'_> {
+    /// The maximum number of unmatched segments that are replaced by a `*` placeholder,
+    /// after which all unmatched segments are replaced by a single `**`.
+    pub const MAX_PLACEHOLDERS: usize = 8;
+
+    /// Returns the redacted path.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Matched(route) => route,
+            Self::Generalized(path) => path,
+        }
+    }
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single URL parameter, consisting of a key and a value.
+///
+/// The key is borrowed from the router and the value from the matched path, see
+/// [`Match`] for details.
+#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Default, Copy, Clone)]
+pub struct Param<'router, 'path> {
+    pub key: &'router [u8],
+    pub value: &'path [u8],
+}
+
+/// The position of a route parameter among the parameters captured by its route, returned
+/// by [`Router::param_index`].
+///
+/// Parameters are always captured in the order they appear in the route, so the position
+/// of a given parameter is fixed for the lifetime of the route. Using an index with
+/// parameters captured by a different route is a logic error, and returns whichever
+/// parameter is at that position.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct ParamIdx(usize);
+
+impl ParamIdx {
+    /// Returns the position of the parameter.
+    pub fn index(self) -> usize {
+        self.0
+    }
+
+    /// Returns the value of the parameter at this position.
+    pub fn get<'path>(self, params: &[Param<'_, 'path>]) -> Option<&'path [u8]> {
+        params.get(self.0).map(|param| param.value)
+    }
+}
+
+/// An iterator over the routes in a router, returned by [`Router::routes`].
+///
+/// Each node tracks the number of routes beneath it, so skipping routes with
+/// [`Iterator::nth`] does not need to visit every route in between.
+#[derive(Debug)]
+pub struct Routes<'r, T> {
+    root: &'r Node<T>,
+    // The range of routes that have not yet been yielded.
+    front: usize,
+    back: usize,
+}
+
+impl<T> Clone for Routes<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root,
+            front: self.front,
+            back: self.back,
+        }
+    }
+}
+
+impl<'r, T> Routes<'r, T> {
+    fn route(&self, n: usize) -> Option<(String, &'r T)> {
+        let (route, node) = self.root.nth_route(n)?;
+        Some((String::from_utf8(route).unwrap(), node.value_ref()?))
+    }
+}
+
+impl<'r, T> Iterator for Routes<'r, T> {
+    type Item = (String, &'r T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.front += 1;
+        self.route(self.front - 1)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.front = self.front.saturating_add(n).min(self.back);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<T> DoubleEndedIterator for Routes<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        self.route(self.back)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.back = self.back.saturating_sub(n).max(self.front);
+        self.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Routes<'_, T> {}
+
+impl<T> FusedIterator for Routes<'_, T> {}
@@ -0,0 +1,37 @@
+use crate::tree::{Node, Tracer};
+
+/// Statistics about a single search, returned by
+/// [`Router::at_with_stats`](crate::Router::at_with_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MatchStats {
+    /// The number of node prefixes that were compared against the path.
+    pub nodes_visited: u32,
+
+    /// The number of times the search backtracked to a skipped route parameter.
+    pub backtracks: u32,
+
+    /// The number of route parameters captured, including any captured by routes that
+    /// were later abandoned by backtracking.
+    pub param_captures: u32,
+
+    /// The time spent searching the router, in nanoseconds.
+    pub match_duration_ns: u64,
+}
+
+impl<'node, 'path, T> Tracer<'node, 'path, T> for MatchStats {
+    fn compare(&mut self, _node: &'node Node<T>, _path: &'path [u8], _matched: bool) {
+        self.nodes_visited += 1;
+    }
+
+    fn backtrack(&mut self, _path: &'path [u8]) {
+        self.backtracks += 1;
+    }
+
+    fn param(&mut self, _value: &'path [u8]) {
+        self.param_captures += 1;
+    }
+
+    fn catch_all(&mut self, _value: &'path [u8]) {
+        self.param_captures += 1;
+    }
+}
@@ -0,0 +1,171 @@
+//! Utilities for testing code that handles router errors.
+//!
+//! Some errors, such as [`MatchError::MissingTrailingSlash`], only occur with a particular
+//! router configuration and can be hard to trigger from integration tests. A
+//! [`FailingRouter`] wraps a router and returns programmed errors instead, so that every
+//! error path of the calling code can be exercised.
+
+use crate::{InsertError, Match, MatchError, Router, StripError};
+
+use std::collections::{HashMap, VecDeque};
+use std::ops::{Deref, DerefMut};
+
+/// A router that can be programmed to fail.
+///
+/// Paths that match a route registered with [`FailingRouter::fail_at`] return the given
+/// error, taking precedence over the wrapped router. Insertions and prefix stripping can
+/// be failed in the same way. Everything else is delegated to the wrapped router, which
+/// can also be accessed directly through [`Deref`].
+///
+/// ```
+/// use matchit::test_util::FailingRouter;
+/// use matchit::{InsertError, MatchError, Router};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut router = FailingRouter::new(Router::new());
+/// router.insert("/slow/{*rest}", "slow")?;
+/// router.fail_at("/slow/{*rest}", MatchError::NotFound)?;
+/// router.fail_next_insert(InsertError::InvalidParam);
+///
+/// assert_eq!(router.at("/slow/query").unwrap_err(), MatchError::NotFound);
+/// assert_eq!(router.insert("/fast", "fast"), Err(InsertError::InvalidParam));
+/// assert_eq!(router.insert("/fast", "fast"), Ok(()));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FailingRouter<T> {
+    router: Router<T>,
+    // The errors returned for paths matching each route.
+    match_errors: Router<MatchError>,
+    // The errors returned when inserting a specific route.
+    insert_errors: HashMap<String, InsertError>,
+    // The errors returned by the next insertions, in order.
+    next_insert_errors: VecDeque<InsertError>,
+    // The errors returned by the next calls to `strip_prefix_from_all`, in order.
+    next_strip_errors: VecDeque<StripError>,
+}
+
+impl<T> FailingRouter<T> {
+    /// Wraps the given router.
+    pub fn new(router: Router<T>) -> Self {
+        Self {
+            router,
+            match_errors: Router::new(),
+            insert_errors: HashMap::new(),
+            next_insert_errors: VecDeque::new(),
+            next_strip_errors: VecDeque::new(),
+        }
+    }
+
+    /// Returns the wrapped router.
+    pub fn into_inner(self) -> Router<T> {
+        self.router
+    }
+
+    /// Returns `error` when matching any path that matches `route`.
+    ///
+    /// Failing routes follow the usual matching rules among themselves, but are
+    /// independent of the routes in the wrapped router.
+    pub fn fail_at(
+        &mut self,
+        route: impl Into<String>,
+        error: MatchError,
+    ) -> Result<(), InsertError> {
+        self.match_errors.insert(route, error)
+    }
+
+    /// Returns `error` every time `route` is inserted, without inserting it.
+    pub fn fail_insert(&mut self, route: impl Into<String>, error: InsertError) {
+        self.insert_errors.insert(route.into(), error);
+    }
+
+    /// Returns `error` from the next insertion of any route, without inserting it.
+    ///
+    /// Errors are queued, so calling this multiple times fails as many insertions.
+    pub fn fail_next_insert(&mut self, error: InsertError) {
+        self.next_insert_errors.push_back(error);
+    }
+
+    /// Returns `error` from the next call to [`FailingRouter::strip_prefix_from_all`],
+    /// without modifying the router.
+    ///
+    /// Errors are queued, so calling this multiple times fails as many calls.
+    pub fn fail_next_strip(&mut self, error: StripError) {
+        self.next_strip_errors.push_back(error);
+    }
+
+    /// Removes all programmed errors.
+    pub fn clear_failures(&mut self) {
+        self.match_errors = Router::new();
+        self.insert_errors.clear();
+        self.next_insert_errors.clear();
+        self.next_strip_errors.clear();
+    }
+
+    /// Inserts a route into the wrapped router, unless a programmed error applies.
+    ///
+    /// See [`Router::insert`].
+    pub fn insert(&mut self, route: impl Into<String>, value: T) -> Result<(), InsertError> {
+        if let Some(error) = self.next_insert_errors.pop_front() {
+            return Err(error);
+        }
+
+        let route = route.into();
+        if let Some(error) = self.insert_errors.get(&route) {
+            return Err(error.clone());
+        }
+
+        self.router.insert(route, value)
+    }
+
+    /// Matches a path against the wrapped router, unless a programmed error applies.
+    ///
+    /// See [`Router::at`].
+    pub fn at<'path>(&self, path: &'path str) -> Result<Match<'_, 'path, &T>, MatchError> {
+        if let Ok(error) = self.match_errors.at(path) {
+            return Err(*error.value);
+        }
+
+        self.router.at(path)
+    }
+
+    /// Matches a path against the wrapped router, unless a programmed error applies.
+    ///
+    /// See [`Router::at_mut`].
+    pub fn at_mut<'path>(
+        &mut self,
+        path: &'path str,
+    ) -> Result<Match<'_, 'path, &mut T>, MatchError> {
+        if let Ok(error) = self.match_errors.at(path) {
+            return Err(*error.value);
+        }
+
+        self.router.at_mut(path)
+    }
+
+    /// Strips a prefix from the routes in the wrapped router, unless a programmed error
+    /// applies.
+    ///
+    /// See [`Router::strip_prefix_from_all`].
+    pub fn strip_prefix_from_all(&mut self, prefix: &str) -> Result<usize, StripError> {
+        if let Some(error) = self.next_strip_errors.pop_front() {
+            return Err(error);
+        }
+
+        self.router.strip_prefix_from_all(prefix)
+    }
+}
+
+impl<T> Deref for FailingRouter<T> {
+    type Target = Router<T>;
+
+    fn deref(&self) -> &Router<T> {
+        &self.router
+    }
+}
+
+impl<T> DerefMut for FailingRouter<T> {
+    fn deref_mut(&mut self) -> &mut Router<T> {
+        &mut self.router
+    }
+}
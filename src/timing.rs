@@ -0,0 +1,32 @@
+use crate::Match;
+
+use std::time::{Duration, Instant};
+
+/// A source of the current time, used by [`Router::at_with_clock`].
+///
+/// [`Router::at_with_clock`]: crate::Router::at_with_clock
+pub trait Clock {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A [`Clock`] that reads the system's monotonic clock with [`Instant::now`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A successful match along with the time it took, returned by
+/// [`Router::at_with_clock`](crate::Router::at_with_clock).
+#[derive(Debug)]
+pub struct TimedMatch<'router, 'path, V> {
+    /// The match.
+    pub matched: Match<'router, 'path, V>,
+
+    /// The time spent searching the router, as measured by the clock.
+    pub lookup_duration: Duration,
+}
@@ -0,0 +1,153 @@
+use crate::tree::{Node, NodeType, Tracer};
+
+use std::fmt;
+
+/// A step-by-step record of a match attempt, returned by
+/// [`Router::trace_match`](crate::Router::trace_match).
+///
+/// The [`Display`](fmt::Display) implementation prints a human-readable walk of the tree.
+///
+/// ```
+/// use matchit::Router;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut router = Router::new();
+/// router.insert("/users/{id}", "A User")?;
+///
+/// let trace = router.trace_match("/users/978");
+/// assert!(trace.matched);
+/// println!("{trace}");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchTrace<'p> {
+    /// The path that was matched.
+    pub path: &'p str,
+
+    /// The decisions made while searching the tree, in order.
+    pub steps: Vec<TraceStep<'p>>,
+
+    /// Whether a matching route was found.
+    pub matched: bool,
+}
+
+/// A single decision made while matching a path.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceStep<'p> {
+    /// The remaining path was compared against the prefix of a node.
+    Compare {
+        /// The type of the node.
+        node_type: NodeType,
+        /// The prefix of the node.
+        prefix: Vec<u8>,
+        /// The remaining path.
+        path: &'p [u8],
+        /// Whether the path matched the prefix.
+        matched: bool,
+    },
+    /// A wildcard child was skipped in favor of a static child, and may be
+    /// backtracked to later.
+    Skip {
+        /// The remaining path at the skipped node.
+        path: &'p [u8],
+    },
+    /// The search backtracked to a previously skipped wildcard.
+    Backtrack {
+        /// The remaining path at the skipped node.
+        path: &'p [u8],
+    },
+    /// A route parameter matched a path segment.
+    Param {
+        /// The matched segment.
+        value: &'p [u8],
+    },
+    /// A catch-all parameter matched the rest of the path.
+    CatchAll {
+        /// The rest of the path.
+        value: &'p [u8],
+    },
+}
+
+impl<'p> MatchTrace<'p> {
+    pub(crate) fn new(path: &'p str) -> Self {
+        Self {
+            path,
+            steps: Vec::new(),
+            matched: false,
+        }
+    }
+}
+
+impl<'node, 'path, T> Tracer<'node, 'path, T> for MatchTrace<'path> {
+    fn compare(&mut self, node: &'node Node<T>, path: &'path [u8], matched: bool) {
+        self.steps.push(TraceStep::Compare {
+            node_type: node.node_type.clone(),
+            prefix: node.prefix.clone(),
+            path,
+            matched,
+        });
+    }
+
+    fn skip(&mut self, path: &'path [u8]) {
+        self.steps.push(TraceStep::Skip { path });
+    }
+
+    fn backtrack(&mut self, path: &'path [u8]) {
+        self.steps.push(TraceStep::Backtrack { path });
+    }
+
+    fn param(&mut self, value: &'path [u8]) {
+        self.steps.push(TraceStep::Param { value });
+    }
+
+    fn catch_all(&mut self, value: &'path [u8]) {
+        self.steps.push(TraceStep::CatchAll { value });
+    }
+}
+
+impl fmt::Display for MatchTrace<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "matching `{}`", self.path)?;
+
+        for step in &self.steps {
+            write!(f, "  ")?;
+
+            match step {
+                TraceStep::Compare {
+                    node_type,
+                    prefix,
+                    path,
+                    matched,
+                } => writeln!(
+                    f,
+                    "compare {:?} prefix `{}` with `{}`: {}",
+                    node_type,
+                    String::from_utf8_lossy(prefix),
+                    String::from_utf8_lossy(path),
+                    if *matched { "match" } else { "mismatch" }
+                )?,
+                TraceStep::Skip { path } => {
+                    writeln!(f, "skip wildcard at `{}`", String::from_utf8_lossy(path))?
+                }
+                TraceStep::Backtrack { path } => writeln!(
+                    f,
+                    "backtrack to wildcard at `{}`",
+                    String::from_utf8_lossy(path)
+                )?,
+                TraceStep::Param { value } => {
+                    writeln!(f, "capture param `{}`", String::from_utf8_lossy(value))?
+                }
+                TraceStep::CatchAll { value } => {
+                    writeln!(f, "capture catch-all `{}`", String::from_utf8_lossy(value))?
+                }
+            }
+        }
+
+        if self.matched {
+            write!(f, "found")
+        } else {
+            write!(f, "not found")
+        }
+    }
+}
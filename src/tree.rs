@@ -1,8 +1,9 @@
-use crate::{InsertError, MatchError, Param};
+use crate::{CheckedInsertError, InsertError, MatchError, Param};
 
 use std::cell::UnsafeCell;
-use std::cmp::min;
+use std::cmp::{min, Reverse};
 use std::ops::Range;
+use std::sync::Arc;
 use std::{fmt, mem};
 
 /// A radix tree used for URL path matching.
@@ -25,11 +26,28 @@ pub struct Node<T> {
     // The value stored at this node.
     //
     // See `Node::at` for why an `UnsafeCell` is necessary.
-    value: Option<UnsafeCell<T>>,
+    pub(crate) value: Option<UnsafeCell<T>>,
     // Parameter name remapping, stored at nodes that hold values.
     pub remapping: ParamRemapping,
+    // The router generation at which the value was inserted.
+    pub(crate) generation: u64,
+    // A hash of the route the value was inserted with, see `template_hash`.
+    pub(crate) template_hash: u64,
+    // The route the value was inserted with, as it was inserted.
+    pub(crate) route: Box<str>,
+    // Whether matching the value is disabled.
+    //
+    // Disabled values are skipped during a search as if they did not exist.
+    pub(crate) disabled: bool,
+    // Path prefixes that a catch-all value does not match, see `Node::excludes`.
+    pub(crate) exclusions: Vec<Vec<u8>>,
+    // Checks the parameter values of a match, see `Router::insert_with_validator`.
+    pub(crate) validator: Option<Validator>,
 }
 
+/// A predicate over the parameter values of a match.
+pub(crate) type Validator = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
 /// The types of nodes a tree can hold.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
 pub enum NodeType {
@@ -51,6 +69,39 @@ unsafe impl<T: Sync> Sync for Node<T> {}
 impl<T> Node<T> {
     // Insert a route into the tree.
     pub fn insert(&mut self, route: String, val: T) -> Result<(), InsertError> {
+        self.insert_with_generation(route, val, 0)
+    }
+
+    // Insert a route into the tree, recording the generation it was inserted at.
+    pub(crate) fn insert_with_generation(
+        &mut self,
+        route: String,
+        val: T,
+        generation: u64,
+    ) -> Result<(), InsertError> {
+        // The value is only taken once the insertion succeeds, so that it is dropped
+        // here after the tree is consistent, even if dropping it panics.
+        let mut val = Some(val);
+        let result = self.insert_inner(route, &mut val, generation);
+
+        // Priorities are incremented while walking down the tree, so they have to be
+        // recalculated if the insertion failed partway through.
+        if result.is_err() {
+            self.recount_priorities();
+        }
+
+        drop(val);
+        result
+    }
+
+    fn insert_inner(
+        &mut self,
+        route: String,
+        val: &mut Option<T>,
+        generation: u64,
+    ) -> Result<(), InsertError> {
+        let hash = template_hash(route.as_bytes());
+        let mut original = Box::<str>::from(route.as_str());
         let route = route.into_bytes().to_vec();
         let (route, remapping) = normalize_params(route)?;
         let mut remaining: &[u8] = &route;
@@ -58,9 +109,12 @@ impl<T> Node<T> {
         self.priority += 1;
 
         // If the tree is empty, insert the root node.
-        if self.prefix.is_empty() && self.children.is_empty() {
+        if self.prefix.is_empty() && self.children.is_empty() && self.value.is_none() {
             let last = self.insert_route(remaining, val)?;
             last.remapping = remapping;
+            last.generation = generation;
+            last.template_hash = hash;
+            last.route = original;
             self.node_type = NodeType::Root;
             return Ok(());
         }
@@ -86,6 +140,12 @@ impl<T> Node<T> {
                     wild_child: current.wild_child,
                     children: mem::take(&mut current.children),
                     remapping: mem::take(&mut current.remapping),
+                    generation: mem::take(&mut current.generation),
+                    template_hash: mem::take(&mut current.template_hash),
+                    route: mem::take(&mut current.route),
+                    disabled: mem::take(&mut current.disabled),
+                    exclusions: mem::take(&mut current.exclusions),
+                    validator: current.validator.take(),
                     priority: current.priority - 1,
                     node_type: NodeType::Static,
                 };
@@ -105,8 +165,11 @@ impl<T> Node<T> {
                 }
 
                 // Insert the value.
-                current.value = Some(UnsafeCell::new(val));
+                current.value = val.take().map(UnsafeCell::new);
                 current.remapping = remapping;
+                current.generation = generation;
+                current.template_hash = hash;
+                current.route = mem::take(&mut original);
                 return Ok(());
             }
 
@@ -140,13 +203,21 @@ impl<T> Node<T> {
             //
             // If we're not inserting a wildcard we have to create a child.
             if (!matches!(next, b'{')) && current.node_type != NodeType::CatchAll {
-                current.indices.push(next);
+                // Route parameters have a single child without an index, like the children
+                // created by `Node::insert_route`.
+                if current.node_type != NodeType::Param {
+                    current.indices.push(next);
+                }
+
                 let mut child = current.add_child(Node::default());
                 child = current.update_child_priority(child);
 
                 // Insert into the newly created node.
                 let last = current.children[child].insert_route(remaining, val)?;
                 last.remapping = remapping;
+                last.generation = generation;
+                last.template_hash = hash;
+                last.route = original;
                 return Ok(());
             }
 
@@ -177,6 +248,9 @@ impl<T> Node<T> {
             // Otherwise, create a new node for the wildcard and insert the route.
             let last = current.insert_route(remaining, val)?;
             last.remapping = remapping;
+            last.generation = generation;
+            last.template_hash = hash;
+            last.route = original;
             return Ok(());
         }
     }
@@ -197,13 +271,20 @@ impl<T> Node<T> {
             // If the root node has no children, we can reset it.
             if self.children.is_empty() {
                 *self = Node::default();
+            } else if value.is_some() {
+                self.priority -= 1;
+                self.disabled = false;
+                self.validator = None;
             }
 
             return value;
         }
 
-        let mut current = self;
-        'walk: loop {
+        // The indices of the children we traversed, used to update priorities.
+        let mut visited = Vec::new();
+
+        let mut current = &mut *self;
+        let value = 'walk: loop {
             // The path is longer than this node's prefix, search deeper.
             if remaining.len() > current.prefix.len() {
                 let (prefix, rest) = remaining.split_at(current.prefix.len());
@@ -217,10 +298,11 @@ impl<T> Node<T> {
                     if current.children.len() == 1 {
                         // The route matches, remove the node.
                         if current.children[0].prefix == remaining {
-                            return current.remove_child(0, &remapping);
+                            break 'walk current.remove_child(0, &remapping);
                         }
 
                         // Otherwise, continue searching.
+                        visited.push(0);
                         current = &mut current.children[0];
                         continue 'walk;
                     }
@@ -229,24 +311,29 @@ impl<T> Node<T> {
                     if let Some(i) = current.indices.iter().position(|&c| c == first) {
                         // The route matches, remove the node.
                         if current.children[i].prefix == remaining {
-                            return current.remove_child(i, &remapping);
+                            break 'walk current.remove_child(i, &remapping);
                         }
 
                         // Otherwise, continue searching.
+                        visited.push(i);
                         current = &mut current.children[i];
                         continue 'walk;
                     }
 
-                    // If the node has a matching wildcard child, continue searching in the child.
-                    if current.wild_child
-                        && remaining.first().zip(remaining.get(2)) == Some((&b'{', &b'}'))
-                    {
+                    // If the node has a wildcard child, continue searching in the child.
+                    //
+                    // Both route parameters and catch-alls start with `{`, which is never
+                    // used as the index of a static child.
+                    if current.wild_child && first == b'{' {
+                        let i = current.children.len() - 1;
+
                         // The route matches, remove the node.
-                        if current.children.last_mut().unwrap().prefix == remaining {
-                            return current.remove_child(current.children.len() - 1, &remapping);
+                        if current.children[i].prefix == remaining {
+                            break 'walk current.remove_child(i, &remapping);
                         }
 
-                        current = current.children.last_mut().unwrap();
+                        visited.push(i);
+                        current = &mut current.children[i];
                         continue 'walk;
                     }
                 }
@@ -254,7 +341,217 @@ impl<T> Node<T> {
 
             // Could not find a match.
             return None;
+        };
+
+        // Update the priorities of the nodes leading up to the removed route.
+        if value.is_some() {
+            let mut current = self;
+            current.priority -= 1;
+
+            for i in visited {
+                current.children[i].priority -= 1;
+                let i = current.reorder_child(i);
+                current = &mut current.children[i];
+            }
+        }
+
+        value
+    }
+
+    // Returns the indices of the children leading to the node with the given normalized
+    // route, if it exists.
+    fn route_indices(&self, route: &[u8]) -> Option<Vec<usize>> {
+        let mut indices = Vec::new();
+        let mut remaining = route;
+        let mut current = self;
+
+        loop {
+            // Found the node.
+            if remaining == current.prefix {
+                return Some(indices);
+            }
+
+            // The route is longer than this node's prefix, search deeper.
+            if remaining.len() <= current.prefix.len() || !remaining.starts_with(&current.prefix) {
+                return None;
+            }
+
+            remaining = &remaining[current.prefix.len()..];
+            let first = remaining[0];
+
+            let i = match current.indices.iter().position(|&c| c == first) {
+                // Continue searching in the matching static child.
+                Some(i) => i,
+                // Continue searching in the wildcard child.
+                None if current.wild_child && first == b'{' => current.children.len() - 1,
+                // Route parameters have a single child without an index.
+                None if current.children.len() == 1 => 0,
+                None => return None,
+            };
+
+            indices.push(i);
+            current = &current.children[i];
+        }
+    }
+
+    // Returns the node with the given normalized route, if it exists.
+    fn find_node(&self, route: &[u8]) -> Option<&Node<T>> {
+        let mut current = self;
+        for i in self.route_indices(route)? {
+            current = &current.children[i];
+        }
+
+        Some(current)
+    }
+
+    /// Returns the route registered with the same shape as the given route, ignoring the
+    /// names of any route parameters.
+    pub(crate) fn find_route(&self, route: String) -> Option<String> {
+        let (mut route, _) = normalize_params(route.into_bytes()).ok()?;
+        let node = self.find_node(&route)?;
+
+        node.value.as_ref()?;
+        denormalize_params(&mut route, &node.remapping);
+        Some(String::from_utf8(route).unwrap())
+    }
+
+    /// Returns the value registered with the same shape as the given route, ignoring the
+    /// names of any route parameters.
+    pub(crate) fn route_value(&self, route: String) -> Option<&T> {
+        let (route, _) = normalize_params(route.into_bytes()).ok()?;
+        self.find_node(&route)?.value_ref()
+    }
+
+    /// Returns a mutable reference to the value registered with exactly the given route,
+    /// including the names of any route parameters.
+    pub(crate) fn route_value_mut(&mut self, route: String) -> Option<&mut T> {
+        let (route, remapping) = normalize_params(route.into_bytes()).ok()?;
+
+        let mut current = self;
+        for i in current.route_indices(&route)? {
+            current = &mut current.children[i];
+        }
+
+        if current.remapping != remapping {
+            return None;
+        }
+
+        current.value.as_mut().map(UnsafeCell::get_mut)
+    }
+
+    /// Returns the position of the parameter named `key` among the parameters captured by
+    /// the given route, if the route exists with exactly the same parameter names.
+    pub(crate) fn param_index(&self, route: String, key: &[u8]) -> Option<usize> {
+        let (route, remapping) = normalize_params(route.into_bytes()).ok()?;
+
+        let node = self.find_node(&route)?;
+        if node.value.is_none() || node.remapping != remapping {
+            return None;
+        }
+
+        if let Some(i) = remapping.iter().position(|param| param == key) {
+            return Some(i);
+        }
+
+        // Catch-all parameters are not normalized, and always come last.
+        let start = route.windows(2).position(|w| w == b"{*")?;
+        (&route[start + 2..route.len() - 1] == key).then_some(remapping.len())
+    }
+
+    /// Enables or disables matching the given route, returning `false` if the route
+    /// does not exist.
+    ///
+    /// Like `Node::remove`, the route parameters must match exactly.
+    pub(crate) fn set_enabled(&mut self, route: String, enabled: bool) -> bool {
+        let (route, remapping) = match normalize_params(route.into_bytes()) {
+            Ok(route) => route,
+            Err(_) => return false,
+        };
+
+        let indices = match self.route_indices(&route) {
+            Some(indices) => indices,
+            None => return false,
+        };
+
+        let mut current = self;
+        for i in indices {
+            current = &mut current.children[i];
+        }
+
+        if current.value.is_none() || current.remapping != remapping {
+            return false;
+        }
+
+        current.disabled = !enabled;
+        true
+    }
+
+    /// Sets the path prefixes excluded from matching the given catch-all route, returning
+    /// `false` if the route does not exist.
+    pub(crate) fn set_exclusions(&mut self, route: String, exclusions: Vec<Vec<u8>>) -> bool {
+        let (route, _) = match normalize_params(route.into_bytes()) {
+            Ok(route) => route,
+            Err(_) => return false,
+        };
+
+        let indices = match self.route_indices(&route) {
+            Some(indices) => indices,
+            None => return false,
+        };
+
+        let mut current = self;
+        for i in indices {
+            current = &mut current.children[i];
+        }
+
+        if current.node_type != NodeType::CatchAll || current.value.is_none() {
+            return false;
+        }
+
+        current.exclusions = exclusions;
+        true
+    }
+
+    /// Sets the validator for the given route, returning `false` if the route does not
+    /// exist.
+    ///
+    /// Like `Node::remove`, the route parameters must match exactly.
+    pub(crate) fn set_validator(&mut self, route: String, validator: Validator) -> bool {
+        let (route, remapping) = match normalize_params(route.into_bytes()) {
+            Ok(route) => route,
+            Err(_) => return false,
+        };
+
+        let indices = match self.route_indices(&route) {
+            Some(indices) => indices,
+            None => return false,
+        };
+
+        let mut current = self;
+        for i in indices {
+            current = &mut current.children[i];
+        }
+
+        if current.value.is_none() || current.remapping != remapping {
+            return false;
         }
+
+        current.validator = Some(validator);
+        true
+    }
+
+    // Returns `true` if the given path is under one of the prefixes excluded from
+    // matching this node.
+    //
+    // Exclusions are compared against the full path, and only match entire segments,
+    // so `/api` excludes `/api` and `/api/users` but not `/apiary`.
+    fn excludes(&self, path: &[u8]) -> bool {
+        self.exclusions
+            .iter()
+            .any(|prefix| match path.strip_prefix(&prefix[..]) {
+                Some(rest) => rest.is_empty() || rest[0] == b'/' || prefix.ends_with(b"/"),
+                None => false,
+            })
     }
 
     /// Remove the child node at the given index, if the route parameters match.
@@ -290,7 +587,14 @@ impl<T> Node<T> {
         }
         // Otherwise, remove the value but preserve the node.
         else {
-            self.children[i].value.take()
+            let value = self.children[i].value.take();
+            if value.is_some() {
+                self.children[i].priority -= 1;
+                self.children[i].disabled = false;
+                self.children[i].validator = None;
+                self.reorder_child(i);
+            }
+            value
         };
 
         value.map(UnsafeCell::into_inner)
@@ -314,25 +618,64 @@ impl<T> Node<T> {
     // Returns the new index of the node.
     fn update_child_priority(&mut self, i: usize) -> usize {
         self.children[i].priority += 1;
-        let priority = self.children[i].priority;
+        self.reorder_child(i)
+    }
+
+    // Moves the given child node to its position in the canonical order of the children,
+    // after its priority has changed.
+    //
+    // Static children are ordered by descending priority, so that the more popular children
+    // are searched first, with ties broken by their first byte. The priority of a node only
+    // depends on the routes beneath it, so the order does not depend on the order in which
+    // routes were inserted. Wildcard children are always last.
+    //
+    // Returns the new index of the node.
+    fn reorder_child(&mut self, i: usize) -> usize {
+        if i >= self.indices.len() {
+            return i;
+        }
+
+        let key = |node: &Self, i: usize| (Reverse(node.children[i].priority), node.indices[i]);
 
         // Move the node to the front as necessary.
         let mut updated = i;
-        while updated > 0 && self.children[updated - 1].priority < priority {
+        while updated > 0 && key(self, updated - 1) > key(self, updated) {
             self.children.swap(updated - 1, updated);
+            self.indices.swap(updated - 1, updated);
             updated -= 1;
         }
 
-        // Update the position of the indices to match.
-        if updated != i {
-            self.indices[updated..=i].rotate_right(1);
+        // Move the node to the back as necessary.
+        while updated + 1 < self.indices.len() && key(self, updated + 1) < key(self, updated) {
+            self.children.swap(updated, updated + 1);
+            self.indices.swap(updated, updated + 1);
+            updated += 1;
         }
 
         updated
     }
 
+    // Restores the canonical order of all static children, see `Node::reorder_child`.
+    fn sort_children(&mut self) {
+        let statics = self.indices.len();
+        let mut sorted = self
+            .children
+            .drain(..statics)
+            .zip(self.indices.drain(..))
+            .collect::<Vec<_>>();
+        sorted.sort_by_key(|(child, index)| (Reverse(child.priority), *index));
+
+        let (children, indices): (Vec<_>, Vec<_>) = sorted.into_iter().unzip();
+        self.children.splice(..0, children);
+        self.indices = indices;
+    }
+
     // Insert a route at this node.
-    fn insert_route(&mut self, mut prefix: &'_ [u8], val: T) -> Result<&mut Node<T>, InsertError> {
+    fn insert_route(
+        &mut self,
+        mut prefix: &'_ [u8],
+        val: &mut Option<T>,
+    ) -> Result<&mut Node<T>, InsertError> {
         let mut current = self;
 
         loop {
@@ -341,7 +684,7 @@ impl<T> Node<T> {
                 Some(wildcard) => wildcard,
                 // There is no wildcard, simply insert into the current node.
                 None => {
-                    current.value = Some(UnsafeCell::new(val));
+                    current.value = val.take().map(UnsafeCell::new);
                     current.prefix = prefix.to_owned();
                     return Ok(current);
                 }
@@ -364,7 +707,7 @@ impl<T> Node<T> {
                 let child = Self {
                     prefix: prefix.to_owned(),
                     node_type: NodeType::CatchAll,
-                    value: Some(UnsafeCell::new(val)),
+                    value: val.take().map(UnsafeCell::new),
                     priority: 1,
                     ..Self::default()
                 };
@@ -410,12 +753,68 @@ impl<T> Node<T> {
             }
 
             // Finally, insert the value.
-            current.value = Some(UnsafeCell::new(val));
+            current.value = val.take().map(UnsafeCell::new);
             return Ok(current);
         }
     }
 }
 
+/// A visitor for the nodes of a tree, see [`Router::visit`](crate::Router::visit).
+///
+/// All methods default to doing nothing, so implementors only need to handle the
+/// nodes they are interested in. `depth` is the number of ancestors of the node.
+pub trait TreeVisitor<T> {
+    /// Visits a node with a static prefix, including the root node.
+    fn visit_static_node(&mut self, _prefix: &[u8], _depth: usize) {}
+
+    /// Visits a route parameter node, e.g. `{id}`.
+    ///
+    /// Parameters are normalized within the tree, so the name is positional and does
+    /// not necessarily match the name used in any route.
+    fn visit_dynamic_node(&mut self, _prefix: &[u8], _depth: usize) {}
+
+    /// Visits a catch-all parameter node, e.g. `{*path}`.
+    fn visit_wildcard_node(&mut self, _prefix: &[u8], _depth: usize) {}
+
+    /// Visits a value stored in the tree, along with the route it was inserted with.
+    ///
+    /// This is called after the node holding the value is visited.
+    fn visit_value(&mut self, _route: &str, _value: &T) {}
+}
+
+impl<T> Node<T> {
+    /// Performs a depth-first traversal of the tree, calling the given visitor for each
+    /// node and value.
+    ///
+    /// Children are visited in the order they are searched when matching.
+    pub fn visit<V: TreeVisitor<T>>(&self, visitor: &mut V) {
+        self.visit_inner(visitor, &mut Vec::new(), 0);
+    }
+
+    fn visit_inner<V: TreeVisitor<T>>(&self, visitor: &mut V, route: &mut Vec<u8>, depth: usize) {
+        match self.node_type {
+            NodeType::Root | NodeType::Static => visitor.visit_static_node(&self.prefix, depth),
+            NodeType::Param => visitor.visit_dynamic_node(&self.prefix, depth),
+            NodeType::CatchAll => visitor.visit_wildcard_node(&self.prefix, depth),
+        }
+
+        let len = route.len();
+        route.extend_from_slice(&self.prefix);
+
+        if let Some(value) = self.value_ref() {
+            let mut route = route.clone();
+            denormalize_params(&mut route, &self.remapping);
+            visitor.visit_value(std::str::from_utf8(&route).unwrap(), value);
+        }
+
+        for child in &self.children {
+            child.visit_inner(visitor, route, depth + 1);
+        }
+
+        route.truncate(len);
+    }
+}
+
 /// A wildcard node that was skipped during a tree search.
 ///
 /// Contains the state necessary to backtrack to the given node.
@@ -430,18 +829,24 @@ struct Skipped<'n, 'p, T> {
 
 #[rustfmt::skip]
 macro_rules! backtracker {
-    ($skipped_nodes:ident, $path:ident, $current:ident, $params:ident, $backtracking:ident, $walk:lifetime) => {
+    ($skipped_nodes:ident, $path:ident, $current:ident, $params:ident, $backtracking:ident, $tracer:ident, $walk:lifetime) => {
         macro_rules! try_backtrack {
             () => {
                 // Try backtracking to any matching wildcard nodes that we skipped while
                 // traversing the tree.
                 while let Some(skipped) = $skipped_nodes.pop() {
                     if skipped.path.ends_with($path) {
+                        // Give up if the tracer asks us to.
+                        if $tracer.stop() {
+                            return Err(MatchError::NotFound);
+                        }
+
                         // Restore the search state.
                         $path = skipped.path;
                         $current = &skipped.node;
                         $params.truncate(skipped.params);
                         $backtracking = true;
+                        $tracer.backtrack($path);
                         continue $walk;
                     }
                 }
@@ -450,6 +855,174 @@ macro_rules! backtracker {
     };
 }
 
+/// Observes the decisions made while searching the tree.
+///
+/// All methods default to doing nothing, so the untraced search is not affected.
+pub(crate) trait Tracer<'node, 'path, T> {
+    // The remaining path was compared against the prefix of a node.
+    fn compare(&mut self, _node: &'node Node<T>, _path: &'path [u8], _matched: bool) {}
+
+    // A wildcard child was skipped in favor of a static child.
+    fn skip(&mut self, _path: &'path [u8]) {}
+
+    // The search backtracked to a skipped wildcard.
+    fn backtrack(&mut self, _path: &'path [u8]) {}
+
+    // A route parameter matched a path segment.
+    fn param(&mut self, _value: &'path [u8]) {}
+
+    // A catch-all parameter matched the rest of the path.
+    fn catch_all(&mut self, _value: &'path [u8]) {}
+
+    // Returns `false` if the value at the given node should be skipped as if it did not
+    // exist.
+    #[inline]
+    fn accept(&mut self, _node: &'node Node<T>) -> bool {
+        true
+    }
+
+    // Returns `true` if the search should fail instead of backtracking to a skipped
+    // wildcard.
+    #[inline]
+    fn stop(&mut self) -> bool {
+        false
+    }
+}
+
+impl<T> Tracer<'_, '_, T> for () {}
+
+// Skips catch-all values.
+pub(crate) struct SkipCatchAll;
+
+impl<'node, T> Tracer<'node, '_, T> for SkipCatchAll {
+    #[inline]
+    fn accept(&mut self, node: &'node Node<T>) -> bool {
+        node.node_type != NodeType::CatchAll
+    }
+}
+
+// Counts the number of prefix comparisons.
+#[cfg(feature = "profile")]
+impl<'node, T> Tracer<'node, '_, T> for usize {
+    fn compare(&mut self, _node: &'node Node<T>, _path: &[u8], _matched: bool) {
+        *self += 1;
+    }
+}
+
+/// Determines how route parameters are captured while searching the tree.
+///
+/// Capturing parameters is interleaved with the search itself, so the search is generic
+/// over the capture strategy to allow skipping the work entirely when the parameters
+/// are not needed.
+pub(crate) trait Capture<'node, 'path> {
+    // Returns the number of captured parameters.
+    fn len(&self) -> usize;
+
+    // Captures a parameter.
+    fn push(&mut self, key: &'node [u8], value: &'path [u8]);
+
+    // Discards any parameters after the first `len`, used when backtracking.
+    fn truncate(&mut self, len: usize);
+
+    // Restores the original names of the captured parameters.
+    fn remap(&mut self, remapping: &'node ParamRemapping);
+}
+
+// Captures parameters in full.
+impl<'node, 'path> Capture<'node, 'path> for Vec<Param<'node, 'path>> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn push(&mut self, key: &'node [u8], value: &'path [u8]) {
+        self.push(Param { key, value });
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    #[inline]
+    fn remap(&mut self, remapping: &'node ParamRemapping) {
+        self.iter_mut()
+            .map(|param| &mut param.key)
+            .enumerate()
+            .for_each(|(i, key)| *key = &remapping[i]);
+    }
+}
+
+// Ignores parameters entirely.
+impl Capture<'_, '_> for () {
+    #[inline]
+    fn len(&self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn push(&mut self, _key: &[u8], _value: &[u8]) {}
+
+    #[inline]
+    fn truncate(&mut self, _len: usize) {}
+
+    #[inline]
+    fn remap(&mut self, _remapping: &ParamRemapping) {}
+}
+
+/// The maximum number of parameters in a route.
+///
+/// Route parameters are normalized to the names `a` through `z` and then `A`, including
+/// any catch-all parameter at the end of the route.
+pub(crate) const MAX_PARAMS: usize = 27;
+
+// Captures parameters into a fixed-size buffer, without allocating.
+pub(crate) struct ParamBuffer<'node, 'path> {
+    params: [Param<'node, 'path>; MAX_PARAMS],
+    len: usize,
+}
+
+impl<'node, 'path> ParamBuffer<'node, 'path> {
+    pub(crate) fn new() -> Self {
+        Self {
+            params: [Param::default(); MAX_PARAMS],
+            len: 0,
+        }
+    }
+
+    // Returns the captured parameters.
+    pub(crate) fn as_slice(&self) -> &[Param<'node, 'path>] {
+        &self.params[..self.len]
+    }
+}
+
+impl<'node, 'path> Capture<'node, 'path> for ParamBuffer<'node, 'path> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn push(&mut self, key: &'node [u8], value: &'path [u8]) {
+        self.params[self.len] = Param { key, value };
+        self.len += 1;
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    #[inline]
+    fn remap(&mut self, remapping: &'node ParamRemapping) {
+        self.params[..self.len]
+            .iter_mut()
+            .zip(remapping)
+            .for_each(|(param, key)| param.key = key);
+    }
+}
+
 impl<T> Node<T> {
     // Returns the node matching the given path.
     //
@@ -459,30 +1032,46 @@ impl<T> Node<T> {
         &'node self,
         full_path: &'path [u8],
     ) -> Result<(&'node UnsafeCell<T>, Vec<Param<'node, 'path>>), MatchError> {
+        let (node, params) = self.search(full_path, Vec::new(), &mut ())?;
+        Ok((node.value.as_ref().unwrap(), params))
+    }
+
+    // Returns the node matching the given path, capturing route parameters into `params`
+    // and reporting each step of the search to `tracer`.
+    //
+    // The returned node is guaranteed to hold a value.
+    #[inline]
+    pub(crate) fn search<'node, 'path, C>(
+        &'node self,
+        full_path: &'path [u8],
+        mut params: C,
+        tracer: &mut impl Tracer<'node, 'path, T>,
+    ) -> Result<(&'node Node<T>, C), MatchError>
+    where
+        C: Capture<'node, 'path>,
+    {
         let mut current = self;
         let mut path = full_path;
         let mut backtracking = false;
-        let mut params = vec![];
         let mut skipped_nodes: Vec<Skipped<'_, '_, T>> = vec![];
 
         'walk: loop {
             // Initialize the backtracker.
-            backtracker!(skipped_nodes, path, current, params, backtracking, 'walk);
+            backtracker!(skipped_nodes, path, current, params, backtracking, tracer, 'walk);
 
             // Reached the end of the search.
             if path.len() <= current.prefix.len() {
+                let matched = *path == *current.prefix;
+                tracer.compare(current, path, matched);
+
                 // Check for an exact match.
-                if *path == *current.prefix {
+                if matched {
                     // Found the matching value.
-                    if let Some(ref value) = current.value {
+                    if current.value.is_some() && !current.disabled && tracer.accept(current) {
                         // Remap the keys of any route parameters we accumulated during the search.
-                        params
-                            .iter_mut()
-                            .map(|param: &mut Param<'node, 'path>| &mut param.key)
-                            .enumerate()
-                            .for_each(|(i, key)| *key = &current.remapping[i]);
+                        params.remap(&current.remapping);
 
-                        return Ok((value, params));
+                        return Ok((current, params));
                     }
                 }
 
@@ -496,8 +1085,11 @@ impl<T> Node<T> {
             // Otherwise, the path is longer than this node's prefix, search deeper.
             let (prefix, rest) = path.split_at(current.prefix.len());
 
+            let matched = *prefix == *current.prefix;
+            tracer.compare(current, path, matched);
+
             // The prefix does not match.
-            if *prefix != *current.prefix {
+            if !matched {
                 // Try backtracking in case we skipped a wildcard that may match.
                 try_backtrack!();
 
@@ -520,6 +1112,7 @@ impl<T> Node<T> {
                     // We may end up needing to backtrack later in case we do not find a
                     // match.
                     if current.wild_child {
+                        tracer.skip(previous);
                         skipped_nodes.push(Skipped {
                             path: previous,
                             node: current,
@@ -553,33 +1146,27 @@ impl<T> Node<T> {
                         Some(i) => i,
                         // This is the last path segment.
                         None => {
-                            let value = match current.value {
-                                // Found the matching value.
-                                Some(ref value) => value,
-                                None => {
-                                    // Try backtracking in case we skipped a wildcard that may match.
-                                    try_backtrack!();
-
-                                    // Otherwise, there are no matching routes in the tree.
-                                    return Err(MatchError::NotFound);
-                                }
-                            };
+                            if current.value.is_none()
+                                || current.disabled
+                                || !tracer.accept(current)
+                            {
+                                // Try backtracking in case we skipped a wildcard that may match.
+                                try_backtrack!();
+
+                                // Otherwise, there are no matching routes in the tree.
+                                return Err(MatchError::NotFound);
+                            }
 
                             // Store the parameter value.
                             // Parameters are normalized so the key is irrelevant for now.
-                            params.push(Param {
-                                key: b"",
-                                value: path,
-                            });
+                            tracer.param(path);
+                            params.push(b"", path);
 
                             // Remap the keys of any route parameters we accumulated during the search.
-                            params
-                                .iter_mut()
-                                .map(|param| &mut param.key)
-                                .enumerate()
-                                .for_each(|(i, key)| *key = &current.remapping[i]);
+                            params.remap(&current.remapping);
 
-                            return Ok((value, params));
+                            // Found the matching value.
+                            return Ok((current, params));
                         }
                     };
 
@@ -590,10 +1177,8 @@ impl<T> Node<T> {
                     if let [child] = current.children.as_slice() {
                         // Store the parameter value.
                         // Parameters are normalized so the key is irrelevant for now.
-                        params.push(Param {
-                            key: b"",
-                            value: param,
-                        });
+                        tracer.param(param);
+                        params.push(b"", param);
 
                         // Continue searching.
                         path = rest;
@@ -611,31 +1196,139 @@ impl<T> Node<T> {
                 NodeType::CatchAll => {
                     // Catch-all segments are only allowed at the end of the route, meaning
                     // this node must contain the value.
-                    let value = match current.value {
-                        // Found the matching value.
-                        Some(ref value) => value,
+                    //
+                    // The value may also exclude this path, in which case it is skipped as
+                    // if it did not exist.
+                    if current.value.is_none()
+                        || current.disabled
+                        || current.excludes(full_path)
+                        || !tracer.accept(current)
+                    {
+                        // Try backtracking in case we skipped a wildcard that may match.
+                        try_backtrack!();
+
                         // Otherwise, there are no matching routes in the tree.
-                        None => return Err(MatchError::NotFound),
-                    };
+                        return Err(MatchError::NotFound);
+                    }
 
                     // Remap the keys of any route parameters we accumulated during the search.
-                    params
-                        .iter_mut()
-                        .map(|param| &mut param.key)
-                        .enumerate()
-                        .for_each(|(i, key)| *key = &current.remapping[i]);
+                    params.remap(&current.remapping);
 
                     // Store the final catch-all parameter (`{*...}`).
                     let key = &current.prefix[2..current.prefix.len() - 1];
-                    params.push(Param { key, value: path });
+                    tracer.catch_all(path);
+                    params.push(key, path);
 
-                    return Ok((value, params));
+                    // Found the matching value.
+                    return Ok((current, params));
                 }
                 _ => unreachable!(),
             }
         }
     }
 
+    // Returns a reference to the value stored at this node.
+    pub(crate) fn value_ref(&self) -> Option<&T> {
+        // Safety: We only expose `&mut T` through `&mut self`.
+        self.value.as_ref().map(|value| unsafe { &*value.get() })
+    }
+
+    /// Returns the `n`th route in the tree along with the node holding its value, in
+    /// sorted order.
+    ///
+    /// The priority of each node is the number of routes beneath it, which allows us to
+    /// skip entire subtrees without visiting them.
+    pub(crate) fn nth_route(&self, mut n: usize) -> Option<(Vec<u8>, &Node<T>)> {
+        let mut route = Vec::new();
+        let mut current = self;
+
+        'walk: loop {
+            route.extend_from_slice(&current.prefix);
+
+            if current.value.is_some() {
+                // Found the route.
+                if n == 0 {
+                    denormalize_params(&mut route, &current.remapping);
+                    return Some((route, current));
+                }
+
+                n -= 1;
+            }
+
+            // Children are kept in priority order, so we have to sort them to
+            // maintain a consistent ordering.
+            let mut children = current.children.iter().collect::<Vec<_>>();
+            children.sort_unstable_by(|a, b| a.prefix.cmp(&b.prefix));
+
+            // Find the child containing the route.
+            for child in children {
+                let priority = child.priority as usize;
+
+                if n < priority {
+                    current = child;
+                    continue 'walk;
+                }
+
+                n -= priority;
+            }
+
+            // The tree contains less than `n` routes.
+            return None;
+        }
+    }
+
+    // Recalculates the priority of this node and any children, restoring the order of
+    // the children to match.
+    //
+    // The tree is walked iteratively, so that deep trees cannot overflow the stack. Nodes
+    // are moved out of their parent while their children are visited, and moved back once
+    // their priority is known.
+    fn recount_priorities(&mut self) -> u32 {
+        struct Frame<T> {
+            node: Node<T>,
+            // The children that have not been visited yet.
+            pending: std::vec::IntoIter<Node<T>>,
+            // The children that have been visited, in their original order.
+            visited: Vec<Node<T>>,
+        }
+
+        impl<T> Frame<T> {
+            fn new(mut node: Node<T>) -> Self {
+                let children = mem::take(&mut node.children);
+                Frame {
+                    visited: Vec::with_capacity(children.len()),
+                    pending: children.into_iter(),
+                    node,
+                }
+            }
+        }
+
+        let mut stack = vec![Frame::new(mem::take(self))];
+        loop {
+            let frame = stack.last_mut().unwrap();
+            if let Some(child) = frame.pending.next() {
+                stack.push(Frame::new(child));
+                continue;
+            }
+
+            let Frame {
+                mut node, visited, ..
+            } = stack.pop().unwrap();
+            node.priority = u32::from(node.value.is_some())
+                + visited.iter().map(|child| child.priority).sum::<u32>();
+            node.children = visited;
+            node.sort_children();
+
+            match stack.last_mut() {
+                Some(parent) => parent.visited.push(node),
+                None => {
+                    *self = node;
+                    return self.priority;
+                }
+            }
+        }
+    }
+
     /// Test helper that ensures route priorities are consistent.
     #[cfg(feature = "__test_helpers")]
     pub fn check_priorities(&self) -> Result<u32, (u32, u32)> {
@@ -654,6 +1347,60 @@ impl<T> Node<T> {
 
         Ok(priority)
     }
+
+    /// Test helper that ensures the structure of the tree is consistent, returning a
+    /// description of the first violation.
+    #[cfg(feature = "__test_helpers")]
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.check_priorities()
+            .map_err(|(stored, actual)| format!("priority {stored} should be {actual}"))?;
+
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            let prefix = String::from_utf8_lossy(&node.prefix);
+            let statics = node.indices.len();
+            let wildcards = node.children.len() - statics.min(node.children.len());
+
+            match node.node_type {
+                NodeType::CatchAll if !node.children.is_empty() => {
+                    return Err(format!("catch-all {prefix:?} has children"));
+                }
+                NodeType::Param if statics > 0 || node.children.len() > 1 => {
+                    return Err(format!("parameter {prefix:?} has multiple children"));
+                }
+                NodeType::Param => {}
+                _ if statics > node.children.len() => {
+                    return Err(format!("{prefix:?} has more indices than children"));
+                }
+                _ if wildcards > 1 || node.wild_child != (wildcards == 1) => {
+                    return Err(format!("{prefix:?} has inconsistent wildcard children"));
+                }
+                _ => {}
+            }
+
+            for (i, child) in node.children.iter().enumerate() {
+                let wildcard = matches!(child.node_type, NodeType::Param | NodeType::CatchAll);
+                if node.node_type == NodeType::Param {
+                    if wildcard || child.prefix.first() != Some(&b'/') {
+                        return Err(format!("parameter {prefix:?} has an invalid child"));
+                    }
+                } else if wildcard != (i >= statics) {
+                    return Err(format!("{prefix:?} has misplaced wildcard children"));
+                } else if i < statics && child.prefix.first() != Some(&node.indices[i]) {
+                    return Err(format!("{prefix:?} has a mismatched index at {i}"));
+                }
+            }
+
+            let key = |i: usize| (Reverse(node.children[i].priority), node.indices[i]);
+            if (1..statics).any(|i| key(i - 1) >= key(i)) {
+                return Err(format!("{prefix:?} has children out of order"));
+            }
+
+            stack.extend(&node.children);
+        }
+
+        Ok(())
+    }
 }
 
 /// An ordered list of route parameters keys for a specific route.
@@ -666,11 +1413,17 @@ type ParamRemapping = Vec<Vec<u8>>;
 
 /// Returns `path` with normalized route parameters, and a parameter remapping
 /// to store at the node for this route.
-fn normalize_params(mut path: Vec<u8>) -> Result<(Vec<u8>, ParamRemapping), InsertError> {
+pub(crate) fn normalize_params(
+    mut path: Vec<u8>,
+) -> Result<(Vec<u8>, ParamRemapping), InsertError> {
     let mut start = 0;
     let mut original = ParamRemapping::new();
 
-    // Parameter names are normalized alphabetically.
+    // Every parameter starts with an opening brace, and any other braces are rejected by
+    // `find_wildcard`, so this bounds the number of parameters to normalize.
+    validate_param_count(&path, MAX_PARAMS)?;
+
+    // Parameter names are normalized alphabetically, continuing with uppercase letters.
     let mut next = b'a';
 
     loop {
@@ -691,7 +1444,14 @@ fn normalize_params(mut path: Vec<u8>) -> Result<(Vec<u8>, ParamRemapping), Inse
 
         // We don't need to normalize catch-all parameters, as they are always
         // at the end of a route.
+        //
+        // This is checked before the tree is modified, so that a failed insertion does
+        // not leave behind an empty node.
         if path[wildcard.clone()][1] == b'*' {
+            if wildcard.end != path.len() {
+                return Err(InsertError::InvalidCatchAll);
+            }
+
             start = wildcard.end;
             continue;
         }
@@ -704,16 +1464,107 @@ fn normalize_params(mut path: Vec<u8>) -> Result<(Vec<u8>, ParamRemapping), Inse
         removed.pop();
         original.push(removed);
 
-        next += 1;
-        if next > b'z' {
-            panic!("Too many route parameters.");
-        }
+        next = match next {
+            b'z' => b'A',
+            next => next + 1,
+        };
 
         // Continue the search after the parameter we just normalized.
         start = wildcard.start + 3;
     }
 }
 
+// Returns an error if the route has more than `max` parameters.
+fn validate_param_count(route: &[u8], max: usize) -> Result<(), InsertError> {
+    let count = route.iter().filter(|&&c| c == b'{').count();
+    if count > max {
+        return Err(InsertError::TooManyParams { count, max });
+    }
+
+    Ok(())
+}
+
+// Returns an error if the route exceeds the given limits, before doing any other work.
+pub(crate) fn validate_limits(
+    route: &str,
+    max_len: usize,
+    max_params: usize,
+) -> Result<(), InsertError> {
+    if route.len() > max_len {
+        return Err(InsertError::TemplateTooLong {
+            len: route.len(),
+            max: max_len,
+        });
+    }
+
+    validate_param_count(route.as_bytes(), max_params)
+}
+
+/// The version of the algorithm used by [`template_hash`].
+///
+/// This is incremented whenever the hash of a given route changes.
+pub const TEMPLATE_HASH_VERSION: u32 = 1;
+
+/// Returns a stable hash of the given route.
+///
+/// The hash is the 64-bit FNV-1a hash of the route, so it is stable across processes
+/// and platforms. Stability across crate versions is best-effort, see
+/// [`TEMPLATE_HASH_VERSION`].
+pub fn template_hash(route: &[u8]) -> u64 {
+    route.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns `true` if the given route only contains characters allowed in a URI path by
+/// RFC 3986, outside of any route parameters.
+///
+/// This is the check performed by routers in strict mode, see [`Router::set_strict`].
+/// Other errors, such as malformed route parameters, are not checked.
+///
+/// [`Router::set_strict`]: crate::Router::set_strict
+pub fn is_valid_template_strict(route: &str) -> bool {
+    validate_strict(route).is_ok()
+}
+
+// Checks that every character outside of route parameters is a valid `pchar` or `/`,
+// and that percent-encoded sequences are well-formed.
+pub(crate) fn validate_strict(route: &str) -> Result<(), InsertError> {
+    let bytes = route.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            // Skip over route parameters, malformed parameters are reported on insertion.
+            b'{' => match bytes[i..].iter().position(|&c| c == b'}') {
+                Some(end) => i += end,
+                None => return Ok(()),
+            },
+            b'%' => {
+                let encoded = bytes.get(i + 1..i + 3);
+                if !encoded.is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) {
+                    return Err(InsertError::IllegalCharacter { ch: '%', at: i });
+                }
+
+                i += 2;
+            }
+            b'}' => {}
+            // unreserved / sub-delims / ":" / "@" / "/"
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {}
+            b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => {}
+            b':' | b'@' | b'/' => {}
+            _ => {
+                let ch = route[i..].chars().next().unwrap();
+                return Err(InsertError::IllegalCharacter { ch, at: i });
+            }
+        }
+
+        i += 1;
+    }
+
+    Ok(())
+}
+
 /// Restores `route` to it's original, denormalized form.
 pub fn denormalize_params(route: &mut Vec<u8>, params: &ParamRemapping) {
     let mut start = 0;
@@ -745,6 +1596,74 @@ pub fn denormalize_params(route: &mut Vec<u8>, params: &ParamRemapping) {
     }
 }
 
+/// Checks the syntax of a route, reporting the position of any error.
+///
+/// This is stricter than `find_wildcard`, and also rejects empty routes, braces in
+/// parameter names, and duplicate parameter names.
+pub(crate) fn check_syntax(route: &str) -> Result<(), CheckedInsertError> {
+    if route.is_empty() {
+        return Err(CheckedInsertError::EmptyRoute);
+    }
+
+    let bytes = route.as_bytes();
+
+    // The name and position of every parameter so far.
+    let mut params: Vec<(&str, usize)> = Vec::new();
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'}' => return Err(CheckedInsertError::UnmatchedBrace { pos: i }),
+            b'{' => {}
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+
+        let start = i;
+        let end = match bytes[start..].iter().position(|&c| c == b'}') {
+            Some(len) => start + len,
+            None => return Err(CheckedInsertError::UnclosedBrace { pos: start }),
+        };
+
+        let name = &route[start + 1..end];
+        let (name, catch_all) = match name.strip_prefix('*') {
+            Some(name) => (name, true),
+            None => (name, false),
+        };
+
+        if name.is_empty() {
+            return Err(CheckedInsertError::EmptyParameterName { pos: start });
+        }
+
+        if name.contains(['*', '/', '{']) {
+            return Err(CheckedInsertError::InvalidParameterName { pos: start });
+        }
+
+        if catch_all && end + 1 != bytes.len() {
+            return Err(CheckedInsertError::WildcardNotAtEnd { pos: start });
+        }
+
+        if matches!(bytes.get(end + 1), Some(&c) if c != b'/') {
+            return Err(CheckedInsertError::SuffixAfterParameter { pos: end + 1 });
+        }
+
+        if let Some(&(_, first_pos)) = params.iter().find(|(other, _)| *other == name) {
+            return Err(CheckedInsertError::DuplicateParameterName {
+                name: name.to_owned(),
+                first_pos,
+                second_pos: start,
+            });
+        }
+
+        params.push((name, start));
+        i = end + 1;
+    }
+
+    Ok(())
+}
+
 // Searches for a wildcard segment and checks the path for invalid characters.
 fn find_wildcard(path: &'_ [u8]) -> Result<Option<Range<usize>>, InsertError> {
     for (start, &c) in path.iter().enumerate() {
@@ -794,6 +1713,124 @@ fn find_wildcard(path: &'_ [u8]) -> Result<Option<Range<usize>>, InsertError> {
     Ok(None)
 }
 
+/// A set of bytes that a route can match at a given position.
+#[derive(Clone, Copy)]
+enum Class {
+    // A single static byte.
+    Byte(u8),
+    // Any byte except `/`, matched by route parameters.
+    Segment,
+    // Any byte, matched by catch-all parameters.
+    Any,
+}
+
+impl Class {
+    // Returns `true` if there is a byte matched by both classes.
+    fn intersects(self, other: Class) -> bool {
+        match (self, other) {
+            (Class::Byte(a), Class::Byte(b)) => a == b,
+            (Class::Byte(c), Class::Segment) | (Class::Segment, Class::Byte(c)) => c != b'/',
+            _ => true,
+        }
+    }
+}
+
+// Converts a route into a sequence of byte classes, where repeated classes
+// can match any number of bytes.
+fn route_classes(route: &[u8]) -> Vec<(Class, bool)> {
+    let mut classes = Vec::new();
+    let mut rest = route;
+
+    while let Some(wildcard) = find_wildcard(rest).unwrap() {
+        classes.extend(
+            rest[..wildcard.start]
+                .iter()
+                .map(|&c| (Class::Byte(c), false)),
+        );
+
+        // Route parameters match at least one byte.
+        let class = match rest[wildcard.start + 1] {
+            b'*' => Class::Any,
+            _ => Class::Segment,
+        };
+        classes.extend([(class, false), (class, true)]);
+
+        rest = &rest[wildcard.end..];
+    }
+
+    classes.extend(rest.iter().map(|&c| (Class::Byte(c), false)));
+    classes
+}
+
+/// Returns `true` if there is a path that is matched by both routes.
+pub(crate) fn routes_overlap(a: &[u8], b: &[u8]) -> bool {
+    let (a, b) = (route_classes(a), route_classes(b));
+
+    // Search for a common path by walking both routes in lockstep.
+    let mut visited = vec![false; (a.len() + 1) * (b.len() + 1)];
+    let mut stack = vec![(0, 0)];
+
+    while let Some((i, j)) = stack.pop() {
+        let visit = i * (b.len() + 1) + j;
+        if visited[visit] {
+            continue;
+        }
+        visited[visit] = true;
+
+        // Both routes matched the entire path.
+        if i == a.len() && j == b.len() {
+            return true;
+        }
+
+        // Repeated classes can also match zero bytes.
+        if let Some((_, true)) = a.get(i) {
+            stack.push((i + 1, j));
+        }
+
+        if let Some((_, true)) = b.get(j) {
+            stack.push((i, j + 1));
+        }
+
+        // Otherwise, both routes must match the next byte.
+        if let (Some(&(x, x_repeat)), Some(&(y, y_repeat))) = (a.get(i), b.get(j)) {
+            if x.intersects(y) {
+                stack.push((i + usize::from(!x_repeat), j + usize::from(!y_repeat)));
+            }
+        }
+    }
+
+    false
+}
+
+/// Returns `true` if the route matches a path starting with the given prefix.
+pub(crate) fn route_matches_prefix(route: &[u8], prefix: &[u8]) -> bool {
+    let (mut route, mut prefix) = (route, prefix);
+
+    loop {
+        // The rest of the route can be filled in to complete the path.
+        if prefix.is_empty() {
+            return true;
+        }
+
+        match route {
+            // Catch-all parameters match the rest of the path.
+            [b'{', b'*', ..] => return true,
+            [b'{', ..] => {
+                // Route parameters match up to the next `/`.
+                let end = route.iter().position(|&c| c == b'}').unwrap();
+                let value = prefix.iter().position(|&c| c == b'/');
+                route = &route[end + 1..];
+                prefix = &prefix[value.unwrap_or(prefix.len())..];
+            }
+            [c, rest @ ..] if *c == prefix[0] => {
+                route = rest;
+                prefix = &prefix[1..];
+            }
+            _ => return false,
+        }
+    }
+}
+
 impl<T> Clone for Node<T>
 where
     T: Clone,
@@ -813,11 +1850,49 @@ where
             indices: self.indices.clone(),
             children: self.children.clone(),
             remapping: self.remapping.clone(),
+            generation: self.generation,
+            template_hash: self.template_hash,
+            route: self.route.clone(),
+            disabled: self.disabled,
+            exclusions: self.exclusions.clone(),
+            validator: self.validator.clone(),
             priority: self.priority,
         }
     }
 }
 
+/// Nodes are compared structurally, ignoring when their values were inserted.
+///
+/// The structure of a tree does not depend on the order in which routes were inserted, so
+/// trees containing the same routes compare equal. Removing a route does not merge the
+/// nodes it leaves behind, so a tree that had routes removed may differ from one that
+/// never contained them.
+impl<T> PartialEq for Node<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.prefix == other.prefix
+            && self.priority == other.priority
+            && self.wild_child == other.wild_child
+            && self.indices == other.indices
+            && self.node_type == other.node_type
+            && self.children == other.children
+            && self.value_ref() == other.value_ref()
+            && self.remapping == other.remapping
+            && self.route == other.route
+            && self.disabled == other.disabled
+            && self.exclusions == other.exclusions
+            // Validators cannot be compared, so they are only equal if they are shared.
+            && match (&self.validator, &other.validator) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl<T> Eq for Node<T> where T: Eq {}
+
 impl<T> Default for Node<T> {
     fn default() -> Self {
         Self {
@@ -828,6 +1903,12 @@ impl<T> Default for Node<T> {
             indices: vec![],
             children: vec![],
             value: None,
+            generation: 0,
+            template_hash: 0,
+            route: Box::default(),
+            disabled: false,
+            exclusions: vec![],
+            validator: None,
             priority: 0,
         }
     }
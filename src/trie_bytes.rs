@@ -0,0 +1,303 @@
+//! A compact binary format for the routes in a router.
+//!
+//! See [`Router::export_trie_bytes`].
+//!
+//! The format starts with the magic bytes `MTRE` and a format version, followed by the
+//! nodes of the tree in pre-order. Each node is stored as:
+//!
+//! - its prefix, as a length-prefixed byte string
+//! - the type of the node, as a single byte
+//! - flags for whether the node holds a value and whether the value is disabled
+//! - if the node holds a value, the route it was inserted with and the value encoded as
+//!   JSON, both as length-prefixed byte strings
+//! - the number of children, followed by the children
+//!
+//! Lengths and counts are little-endian `u32`s.
+
+use crate::tree::{normalize_params, NodeType};
+use crate::{InsertError, Router};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::fmt;
+
+const MAGIC: &[u8] = b"MTRE";
+const VERSION: u8 = 1;
+
+const HAS_VALUE: u8 = 0b01;
+const DISABLED: u8 = 0b10;
+
+impl<T> Router<T> {
+    /// Encodes the routes in this router into a compact binary format, see
+    /// [`trie_bytes`](crate::trie_bytes).
+    ///
+    /// Routes are stored along with their values and whether they are disabled. Versioned
+    /// routes, catch-all exclusions, validators, and the router's configuration are not
+    /// stored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value cannot be encoded as JSON, such as a map with non-string keys.
+    ///
+    /// ```
+    /// use matchit::Router;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut router = Router::new();
+    /// router.insert("/users/{id}", 1)?;
+    ///
+    /// let bytes = router.export_trie_bytes();
+    /// let router = Router::<u32>::import_trie_bytes(&bytes)?;
+    /// assert_eq!(*router.at("/users/978")?.value, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn export_trie_bytes(&self) -> Vec<u8>
+    where
+        T: Serialize,
+    {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            write_bytes(&mut bytes, &node.prefix);
+            bytes.push(match node.node_type {
+                NodeType::Root => 0,
+                NodeType::Param => 1,
+                NodeType::CatchAll => 2,
+                NodeType::Static => 3,
+            });
+
+            match node.value_ref() {
+                Some(value) => {
+                    bytes.push(if node.disabled {
+                        HAS_VALUE | DISABLED
+                    } else {
+                        HAS_VALUE
+                    });
+                    write_bytes(&mut bytes, node.route.as_bytes());
+
+                    let value = serde_json::to_vec(value).expect("failed to encode value");
+                    write_bytes(&mut bytes, &value);
+                }
+                None => bytes.push(0),
+            }
+
+            write_len(&mut bytes, node.children.len());
+
+            // Children are popped in order.
+            stack.extend(node.children.iter().rev());
+        }
+
+        bytes
+    }
+
+    /// Decodes a router from bytes returned by [`Router::export_trie_bytes`].
+    ///
+    /// The bytes are checked before the router is built. Every route must agree with its
+    /// position in the tree, and every value must decode as a `T`.
+    pub fn import_trie_bytes(bytes: &[u8]) -> Result<Router<T>, ImportError>
+    where
+        T: DeserializeOwned,
+    {
+        if !bytes.starts_with(MAGIC) || bytes.get(MAGIC.len()) != Some(&VERSION) {
+            return Err(ImportError::InvalidHeader);
+        }
+
+        let mut reader = Reader {
+            bytes,
+            pos: MAGIC.len() + 1,
+        };
+
+        let mut routes = Vec::new();
+
+        // The prefixes of the nodes leading to the current node.
+        let mut path = Vec::new();
+        // The number of children left to read for each node leading to the current node,
+        // along with the length of the path before that node.
+        let mut parents: Vec<(u32, usize)> = Vec::new();
+
+        loop {
+            if let Some((remaining, _)) = parents.last_mut() {
+                *remaining -= 1;
+            }
+
+            let start = path.len();
+            path.extend_from_slice(reader.bytes()?);
+
+            let at = reader.pos;
+            if reader.u8()? > 3 {
+                return Err(ImportError::Malformed { offset: at });
+            }
+
+            let at = reader.pos;
+            match reader.u8()? {
+                0 => {}
+                flags if flags & HAS_VALUE != 0 && flags & !(HAS_VALUE | DISABLED) == 0 => {
+                    let at = reader.pos;
+                    let route = std::str::from_utf8(reader.bytes()?)
+                        .map_err(|_| ImportError::Malformed { offset: at })?
+                        .to_owned();
+
+                    // Routes are stored in the tree with normalized parameters.
+                    match normalize_params(route.clone().into_bytes()) {
+                        Ok((normalized, _)) if normalized == path => {}
+                        _ => return Err(ImportError::Inconsistent { route }),
+                    }
+
+                    let value = serde_json::from_slice(reader.bytes()?).map_err(|err| {
+                        ImportError::Value {
+                            route: route.clone(),
+                            message: err.to_string(),
+                        }
+                    })?;
+
+                    routes.push((route, value, flags & DISABLED != 0));
+                }
+                _ => return Err(ImportError::Malformed { offset: at }),
+            }
+
+            parents.push((reader.u32()?, start));
+
+            // Move on from any nodes whose children have all been read.
+            while let Some(&(0, start)) = parents.last() {
+                parents.pop();
+                path.truncate(start);
+            }
+
+            if parents.is_empty() {
+                break;
+            }
+        }
+
+        if reader.pos != bytes.len() {
+            return Err(ImportError::Malformed { offset: reader.pos });
+        }
+
+        let mut router = Router::new();
+
+        for (route, value, disabled) in routes {
+            if let Err(error) = router.insert(route.clone(), value) {
+                return Err(ImportError::Insert { route, error });
+            }
+
+            if disabled {
+                router.set_enabled(route, false);
+            }
+        }
+
+        Ok(router)
+    }
+}
+
+// Writes a length or count.
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("length does not fit in a u32");
+    bytes.extend_from_slice(&len.to_le_bytes());
+}
+
+// Writes a length-prefixed byte string.
+fn write_bytes(bytes: &mut Vec<u8>, value: &[u8]) {
+    write_len(bytes, value.len());
+    bytes.extend_from_slice(value);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ImportError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..)
+            .and_then(|rest| rest.get(..len))
+            .ok_or(ImportError::UnexpectedEnd)?;
+
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ImportError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ImportError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    // Reads a length-prefixed byte string.
+    fn bytes(&mut self) -> Result<&'a [u8], ImportError> {
+        let len = self.u32()?;
+        self.take(len as usize)
+    }
+}
+
+/// An error returned by [`Router::import_trie_bytes`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ImportError {
+    /// The bytes do not start with the expected header, or use an unsupported version of
+    /// the format.
+    InvalidHeader,
+    /// The bytes ended in the middle of a node.
+    UnexpectedEnd,
+    /// The bytes contain an invalid node at the given offset.
+    Malformed {
+        /// The offset of the first invalid byte.
+        offset: usize,
+    },
+    /// A route does not agree with its position in the tree.
+    Inconsistent {
+        /// The route.
+        route: String,
+    },
+    /// A value could not be decoded.
+    Value {
+        /// The route the value is stored under.
+        route: String,
+        /// A description of the error.
+        message: String,
+    },
+    /// A route could not be inserted into the router.
+    Insert {
+        /// The route.
+        route: String,
+        /// The error returned by [`Router::insert`].
+        error: InsertError,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "Invalid header"),
+            Self::UnexpectedEnd => write!(f, "Unexpected end of input"),
+            Self::Malformed { offset } => write!(f, "Malformed node at offset {}", offset),
+            Self::Inconsistent { route } => {
+                write!(
+                    f,
+                    "Route does not match its position in the tree: {}",
+                    route
+                )
+            }
+            Self::Value { route, message } => {
+                write!(f, "Failed to decode value for route {}: {}", route, message)
+            }
+            Self::Insert { route, error } => {
+                write!(f, "Failed to insert route {}: {}", route, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Insert { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
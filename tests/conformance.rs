@@ -0,0 +1,29 @@
+#![cfg(feature = "conformance")]
+
+use matchit::conformance::{self, rule, CASES};
+use matchit::Router;
+
+#[test]
+fn conformance() {
+    if let Err(failure) = conformance::run(Router::<String>::new) {
+        panic!("{failure}");
+    }
+}
+
+#[test]
+fn rules() {
+    let rules = [
+        rule::STATIC_OVER_PARAM,
+        rule::STATIC_OVER_CATCH_ALL,
+        rule::TRAILING_SLASH,
+        rule::EMPTY_SEGMENT,
+        rule::BACKTRACKING,
+        rule::PARAM_ORDER,
+        rule::RAW_PATH,
+    ];
+
+    // Every rule is covered, and cases are grouped by rule.
+    let mut order = CASES.iter().map(|case| case.rule).collect::<Vec<_>>();
+    order.dedup();
+    assert_eq!(order, rules);
+}
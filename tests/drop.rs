@@ -0,0 +1,97 @@
+use matchit::Router;
+
+use std::panic::{self, AssertUnwindSafe};
+
+// A value that panics when dropped.
+struct PanicOnDrop(&'static str);
+
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            panic!("dropped {}", self.0);
+        }
+    }
+}
+
+fn check(router: &Router<PanicOnDrop>, routes: &[(&str, &str)]) {
+    router.check_priorities().unwrap();
+    assert_eq!(router.len(), routes.len());
+
+    for (path, value) in routes {
+        assert_eq!(router.at(path).unwrap().value.0, *value, "{path}");
+    }
+}
+
+#[test]
+fn panic_on_drop() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", PanicOnDrop("user")).unwrap();
+    router
+        .insert("/users/{id}/posts", PanicOnDrop("posts"))
+        .unwrap();
+    router
+        .insert("/static/{*path}", PanicOnDrop("static"))
+        .unwrap();
+
+    // A failed insertion drops the value after restoring the router.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = router.insert("/users/{id}/posts", PanicOnDrop("conflict"));
+    }));
+    assert!(result.is_err());
+    check(
+        &router,
+        &[
+            ("/users/1", "user"),
+            ("/users/1/posts", "posts"),
+            ("/static/a", "static"),
+        ],
+    );
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = router.insert("/static/{*path}/invalid", PanicOnDrop("invalid"));
+    }));
+    assert!(result.is_err());
+    check(
+        &router,
+        &[
+            ("/users/1", "user"),
+            ("/users/1/posts", "posts"),
+            ("/static/a", "static"),
+        ],
+    );
+
+    // A removed value is dropped after the router is updated.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        router.remove("/users/{id}");
+    }));
+    assert!(result.is_err());
+    check(
+        &router,
+        &[("/users/1/posts", "posts"), ("/static/a", "static")],
+    );
+    assert!(router.at("/users/1").is_err());
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        router.remove("/static/{*path}");
+    }));
+    assert!(result.is_err());
+    check(&router, &[("/users/1/posts", "posts")]);
+    assert!(router.at("/static/a").is_err());
+
+    // The router remains usable.
+    router
+        .insert("/users/{id}", PanicOnDrop("new user"))
+        .unwrap();
+    check(
+        &router,
+        &[("/users/1", "new user"), ("/users/1/posts", "posts")],
+    );
+
+    let routes = router.routes().map(|(route, _)| route).collect::<Vec<_>>();
+    assert_eq!(routes, vec!["/users/{id}", "/users/{id}/posts"]);
+
+    // Avoid panicking when the router itself is dropped.
+    for route in routes {
+        std::mem::forget(router.remove(route));
+    }
+}
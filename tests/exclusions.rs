@@ -0,0 +1,85 @@
+use matchit::{InsertError, Router};
+
+#[test]
+fn exclusions() {
+    let mut router = Router::new();
+    router.insert("/api/users/{id}", "user").unwrap();
+    router.insert("/assets/app.js", "app").unwrap();
+    router
+        .insert_catch_all_with_exclusions("/{*spa}", "spa", &["/api", "/assets/"])
+        .unwrap();
+
+    let cases = [
+        ("/about", Some("spa")),
+        ("/apiary", Some("spa")),
+        ("/assetsx", Some("spa")),
+        ("/api/users/1", Some("user")),
+        ("/assets/app.js", Some("app")),
+        ("/api", None),
+        ("/api/", None),
+        ("/api/unknown", None),
+        // Reached by backtracking from `/api/users/{id}`.
+        ("/api/users/1/posts", None),
+        ("/assets/", None),
+        ("/assets/style.css", None),
+    ];
+
+    for (path, expected) in cases {
+        assert_eq!(router.at(path).ok().map(|m| *m.value), expected, "{path}");
+        assert_eq!(router.value_at(path).copied(), expected, "{path}");
+    }
+
+    router.check_priorities().unwrap();
+}
+
+#[test]
+fn exclusions_fallback() {
+    let mut router = Router::new();
+    router.insert("/{*spa}", "spa").unwrap();
+    router
+        .insert_catch_all_with_exclusions("/docs/{*page}", "docs", &["/docs/internal"])
+        .unwrap();
+
+    // Excluded paths fall back to less specific routes.
+    assert_eq!(*router.at("/docs/guide").unwrap().value, "docs");
+    assert_eq!(*router.at("/docs/internal/x").unwrap().value, "spa");
+
+    let matched = router.at("/docs/internal").unwrap();
+    assert_eq!(*matched.value, "spa");
+    assert_eq!(matched.params[0].key, b"spa");
+    assert_eq!(matched.params[0].value, b"docs/internal");
+
+    // Exclusions are removed along with the route.
+    assert_eq!(router.remove("/docs/{*page}"), Some("docs"));
+    router.insert("/docs/{*page}", "docs").unwrap();
+    assert_eq!(*router.at("/docs/internal/x").unwrap().value, "docs");
+}
+
+#[test]
+fn exclusions_strip_prefix() {
+    let mut router = Router::new();
+    router.insert("/app/api/users", "users").unwrap();
+    router
+        .insert_catch_all_with_exclusions("/app/{*spa}", "spa", &["/app/api"])
+        .unwrap();
+
+    router.strip_prefix_from_all("/app").unwrap();
+    assert_eq!(*router.at("/users").unwrap().value, "spa");
+    assert_eq!(*router.at("/api/users").unwrap().value, "users");
+    assert!(router.at("/api/posts").is_err());
+}
+
+#[test]
+fn exclusions_invalid() {
+    let mut router = Router::new();
+
+    for route in ["/users", "/users/{id}", "/{*rest}/users"] {
+        assert_eq!(
+            router.insert_catch_all_with_exclusions(route, (), &["/api"]),
+            Err(InsertError::InvalidCatchAll),
+            "{route}"
+        );
+    }
+
+    assert!(router.is_empty());
+}
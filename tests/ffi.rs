@@ -0,0 +1,157 @@
+#![cfg(feature = "ffi")]
+
+use matchit::ffi::*;
+
+use std::ptr;
+
+unsafe fn insert(router: *mut MatchitRouter, route: &str, value: u64) -> i32 {
+    matchit_insert(router, route.as_ptr(), route.len(), value)
+}
+
+unsafe fn at(
+    router: *const MatchitRouter,
+    path: &str,
+) -> Result<(u64, Vec<(String, String)>), i32> {
+    let mut value = 0;
+    let mut params = [MatchitParam {
+        key: ptr::null(),
+        key_len: 0,
+        offset: 0,
+        len: 0,
+    }; 4];
+    let mut len = params.len();
+
+    match matchit_at(
+        router,
+        path.as_ptr(),
+        path.len(),
+        &mut value,
+        params.as_mut_ptr(),
+        &mut len,
+    ) {
+        MATCHIT_OK => {}
+        code => return Err(code),
+    }
+
+    let params = params[..len].iter().map(|param| {
+        let key = std::slice::from_raw_parts(param.key, param.key_len);
+        let value = &path[param.offset..param.offset + param.len];
+        (String::from_utf8(key.to_vec()).unwrap(), value.to_owned())
+    });
+
+    Ok((value, params.collect()))
+}
+
+#[test]
+fn ffi() {
+    unsafe {
+        let router = matchit_router_new();
+        assert_eq!(insert(router, "/users/{id}", 1), MATCHIT_OK);
+        assert_eq!(insert(router, "/users/{id}/posts/{post}", 2), MATCHIT_OK);
+        assert_eq!(insert(router, "/static/{*path}", 3), MATCHIT_OK);
+
+        assert_eq!(
+            at(router, "/users/978"),
+            Ok((1, vec![("id".into(), "978".into())]))
+        );
+        assert_eq!(
+            at(router, "/users/1/posts/2"),
+            Ok((
+                2,
+                vec![("id".into(), "1".into()), ("post".into(), "2".into())]
+            ))
+        );
+        assert_eq!(
+            at(router, "/static/a/b"),
+            Ok((3, vec![("path".into(), "a/b".into())]))
+        );
+        assert_eq!(at(router, "/posts"), Err(MATCHIT_ERR_NOT_FOUND));
+
+        // Insertion errors.
+        assert_eq!(insert(router, "/users/{name}", 4), MATCHIT_ERR_CONFLICT);
+        assert_eq!(
+            insert(router, "/{a}x", 4),
+            MATCHIT_ERR_INVALID_PARAM_SEGMENT
+        );
+        assert_eq!(insert(router, "/{}", 4), MATCHIT_ERR_INVALID_PARAM);
+        assert_eq!(
+            insert(router, "/x/{*a}/b", 4),
+            MATCHIT_ERR_INVALID_CATCH_ALL
+        );
+        assert_eq!(
+            matchit_insert(router, b"/\xff".as_ptr(), 2, 4),
+            MATCHIT_ERR_INVALID_UTF8
+        );
+
+        let many = ('a'..='z').chain('A'..='B').map(|c| format!("/{{{c}}}"));
+        assert_eq!(
+            insert(router, &many.collect::<String>(), 4),
+            MATCHIT_ERR_TOO_MANY_PARAMS
+        );
+        let long = format!("/{}", "x".repeat(8 * 1024));
+        assert_eq!(insert(router, &long, 4), MATCHIT_ERR_TEMPLATE_TOO_LONG);
+
+        // The parameter buffer must be large enough.
+        let (mut value, mut len) = (0, 1);
+        let mut param = MatchitParam {
+            key: ptr::null(),
+            key_len: 0,
+            offset: 0,
+            len: 0,
+        };
+        let path = "/users/1/posts/2";
+        assert_eq!(
+            matchit_at(
+                router,
+                path.as_ptr(),
+                path.len(),
+                &mut value,
+                &mut param,
+                &mut len
+            ),
+            MATCHIT_ERR_BUFFER_TOO_SMALL
+        );
+        assert_eq!(len, 2);
+
+        let mut len = 0;
+        let path = "/users/1";
+        assert_eq!(
+            matchit_at(
+                router,
+                path.as_ptr(),
+                path.len(),
+                &mut value,
+                ptr::null_mut(),
+                &mut len
+            ),
+            MATCHIT_ERR_BUFFER_TOO_SMALL
+        );
+
+        // Paths do not need to be valid UTF-8.
+        let mut len = 1;
+        let path = b"/users/\xff";
+        assert_eq!(
+            matchit_at(
+                router,
+                path.as_ptr(),
+                path.len(),
+                &mut value,
+                &mut param,
+                &mut len
+            ),
+            MATCHIT_OK
+        );
+        assert_eq!((value, len, param.offset, param.len), (1, 1, 7, 1));
+
+        // Strict validation.
+        assert_eq!(insert(router, "/a b", 5), MATCHIT_OK);
+        assert_eq!(matchit_router_set_strict(router, true), MATCHIT_OK);
+        assert_eq!(insert(router, "/c d", 5), MATCHIT_ERR_ILLEGAL_CHARACTER);
+
+        assert_eq!(insert(ptr::null_mut(), "/", 0), MATCHIT_ERR_NULL);
+        assert_eq!(at(ptr::null(), "/"), Err(MATCHIT_ERR_NULL));
+
+        matchit_router_free(router);
+        matchit_router_free(ptr::null_mut());
+    }
+}
@@ -0,0 +1,52 @@
+use matchit::Router;
+
+#[test]
+fn generation() {
+    let mut router = Router::new();
+    assert_eq!(router.generation(), 0);
+
+    router.insert("/users/{id}", "user").unwrap();
+    router.insert("/posts", "posts").unwrap();
+    assert_eq!(router.generation(), 2);
+    assert_eq!(router.at("/users/1").unwrap().generation, 1);
+    assert_eq!(router.at("/posts").unwrap().generation, 2);
+
+    // Failed mutations do not affect the generation.
+    assert!(router.insert("/users/{name}", "conflict").is_err());
+    assert_eq!(router.remove("/missing"), None);
+    assert_eq!(router.generation(), 2);
+
+    // Unrelated insertions do not affect existing routes, even if they split a node.
+    router.insert("/users/{id}/posts", "user posts").unwrap();
+    router.insert("/post", "post").unwrap();
+    assert_eq!(router.at("/users/1").unwrap().generation, 1);
+    assert_eq!(router.at("/posts").unwrap().generation, 2);
+    assert_eq!(router.at("/post").unwrap().generation, 4);
+
+    // Replacing a route records the new generation.
+    assert_eq!(router.remove("/users/{id}"), Some("user"));
+    assert_eq!(router.generation(), 5);
+    router.insert("/users/{id}", "new user").unwrap();
+    assert_eq!(router.generation(), 6);
+    assert_eq!(router.at_mut("/users/1").unwrap().generation, 6);
+
+    assert_eq!(
+        router.iter_with_generation().collect::<Vec<_>>(),
+        vec![
+            ("/post".to_owned(), &"post", 4),
+            ("/posts".to_owned(), &"posts", 2),
+            ("/users/{id}".to_owned(), &"new user", 6),
+            ("/users/{id}/posts".to_owned(), &"user posts", 3),
+        ]
+    );
+
+    // The generation keeps increasing across removals and insertions.
+    let mut last = router.generation();
+    for _ in 0..3 {
+        router.remove("/posts").unwrap();
+        router.insert("/posts", "posts").unwrap();
+        assert!(router.generation() > last);
+        last = router.generation();
+    }
+    assert_eq!(router.at("/posts").unwrap().generation, last);
+}
@@ -1,4 +1,10 @@
-use matchit::{InsertError, Router};
+mod common;
+
+use common::Rng;
+use matchit::{
+    CheckedInsertError, ConfigError, InsertError, MatchError, Router, RouterConfig,
+    TrailingSlashBehavior,
+};
 
 struct InsertTest(Vec<(&'static str, Result<(), InsertError>)>);
 
@@ -60,10 +66,18 @@ fn invalid_catchall() {
         ("/src/{*filepath}/x", Err(InsertError::InvalidCatchAll)),
         ("/src2/", Ok(())),
         ("/src2/{*filepath}/x", Err(InsertError::InvalidCatchAll)),
+        ("/src2/{*filepath}", Ok(())),
+        ("/-{*filepath}//", Err(InsertError::InvalidCatchAll)),
+        ("/-", Ok(())),
     ])
     .run();
 }
 
+#[test]
+fn empty_route_conflict() {
+    InsertTest(vec![("", Ok(())), ("", Err(conflict(""))), ("/", Ok(()))]).run();
+}
+
 #[test]
 fn catchall_root_conflict() {
     InsertTest(vec![("/", Ok(())), ("/{*filepath}", Ok(()))]).run();
@@ -219,3 +233,349 @@ fn invalid_param() {
 fn bare_catchall() {
     InsertTest(vec![("{*foo}", Ok(())), ("foo/{*bar}", Ok(()))]).run();
 }
+
+#[test]
+fn from_iter() {
+    let router = Router::from_iter([("/hey", 1), ("/hey/{user}", 2)]).unwrap();
+    assert_eq!(*router.at("/hey").unwrap().value, 1);
+    assert_eq!(*router.at("/hey/x").unwrap().value, 2);
+
+    let routes = vec![
+        ("/a".to_owned(), 1),
+        ("/{x}".to_owned(), 2),
+        ("/{y}".to_owned(), 3),
+    ];
+    assert_eq!(Router::from_iter(routes).unwrap_err(), conflict("/{x}"));
+}
+
+#[test]
+fn build_from_config() {
+    struct Config(Vec<(&'static str, u32)>);
+
+    impl RouterConfig<u32> for Config {
+        type Routes = Vec<(String, u32)>;
+
+        fn routes(self) -> Self::Routes {
+            self.0
+                .into_iter()
+                .map(|(route, value)| (route.to_owned(), value))
+                .collect()
+        }
+    }
+
+    let router = Router::build_from_config(Config(vec![("/hey", 1), ("/hey/{user}", 2)])).unwrap();
+    assert_eq!(*router.at("/hey/x").unwrap().value, 2);
+
+    let err = Router::build_from_config(Config(vec![("/{x}", 1), ("/{y}", 2)])).unwrap_err();
+    assert_eq!(
+        err,
+        ConfigError::Insert {
+            route: "/{y}".to_owned(),
+            error: conflict("/{x}"),
+        }
+    );
+    assert_eq!(
+        std::error::Error::source(&err).unwrap().to_string(),
+        conflict("/{x}").to_string()
+    );
+}
+
+#[test]
+fn builder() {
+    let router = Router::builder()
+        .with_trailing_slash_behavior(TrailingSlashBehavior::Redirect)
+        .insert("/hey", 1)
+        .insert("/hey/{user}", 2)
+        .build()
+        .unwrap();
+    assert_eq!(*router.at("/hey/x").unwrap().value, 2);
+    assert_eq!(
+        router.at("/hey/").unwrap_err(),
+        MatchError::ExtraTrailingSlash
+    );
+
+    // Every failed route is reported, in order.
+    let errors = Router::builder()
+        .with_strict(true)
+        .with_max_template_len(16)
+        .with_max_params(1)
+        .insert("/{x}", 1)
+        .insert("/{y}", 2)
+        .insert("/a b", 3)
+        .insert("/{a}/{b}", 4)
+        .insert("/a/very/long/route", 5)
+        .insert("/ok", 6)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        [
+            conflict("/{x}"),
+            InsertError::IllegalCharacter { ch: ' ', at: 2 },
+            InsertError::TooManyParams { count: 2, max: 1 },
+            InsertError::TemplateTooLong { len: 18, max: 16 },
+        ]
+    );
+
+    assert!(Router::<()>::builder().build().unwrap().is_empty());
+}
+
+#[test]
+fn strict() {
+    let cases = [
+        // unreserved
+        ("/AZaz09-._~", Ok(())),
+        // sub-delims, ':' and '@'
+        ("/!$&'()*+,;=:@", Ok(())),
+        // percent-encoded
+        ("/a%20b%2F%ff", Ok(())),
+        ("/%", Err(('%', 1))),
+        ("/%2", Err(('%', 1))),
+        ("/%2g", Err(('%', 1))),
+        ("/a%%20", Err(('%', 2))),
+        // illegal characters
+        ("/a b", Err((' ', 2))),
+        ("/\"", Err(('"', 1))),
+        ("/a?b", Err(('?', 2))),
+        ("/a#b", Err(('#', 2))),
+        ("/[x]", Err(('[', 1))),
+        ("/a\\b", Err(('\\', 2))),
+        ("/a^b", Err(('^', 2))),
+        ("/a|b", Err(('|', 2))),
+        ("/a`b", Err(('`', 2))),
+        ("/<a>", Err(('<', 1))),
+        ("/a\tb", Err(('\t', 2))),
+        ("/café", Err(('é', 4))),
+        // route parameters are not validated
+        ("/{a b}", Ok(())),
+        ("/x/{ é }/{*rest}", Ok(())),
+        ("/y-{%}/z y", Err((' ', 8))),
+    ];
+
+    for (route, expected) in cases {
+        let expected = expected.map_err(|(ch, at)| InsertError::IllegalCharacter { ch, at });
+        assert_eq!(matchit::is_valid_template_strict(route), expected.is_ok());
+
+        let mut router = Router::new();
+        router.set_strict(true);
+        assert_eq!(router.insert(route, ()), expected, "{route}");
+    }
+
+    // Other errors are reported as usual.
+    let mut router = Router::new();
+    router.set_strict(true);
+    assert_eq!(router.insert("/{a", ()), Err(InsertError::InvalidParam));
+    assert_eq!(router.insert("/a}", ()), Err(InsertError::InvalidParam));
+
+    // The default is permissive.
+    let mut router = Router::new();
+    assert_eq!(router.insert("/a b", ()), Ok(()));
+}
+
+#[test]
+fn canonical_order() {
+    let routes = [
+        "/",
+        "/users",
+        "/users/{id}",
+        "/users/{id}/posts",
+        "/users/{id}/posts/{post}",
+        "/users/new",
+        "/u",
+        "/about",
+        "/api/v1/{*rest}",
+        "/api/v2/users",
+        "/api/v2/posts",
+        "/b/1",
+        "/b/2",
+        "/c/1",
+        "/c/2",
+        "/static/{*file}",
+        "/{page}",
+    ];
+
+    let build = |routes: &[&str]| {
+        let mut router = Router::new();
+        for route in routes {
+            router.insert(*route, route.to_string()).unwrap();
+        }
+        router
+    };
+
+    let expected = build(&routes);
+
+    // Insert the routes in a number of pseudo-random orders.
+    let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+    for _ in 0..100 {
+        let mut shuffled = routes;
+        for i in (1..shuffled.len()).rev() {
+            shuffled.swap(i, rng.below(i + 1));
+        }
+
+        let router = build(&shuffled);
+        assert_eq!(router.root, expected.root, "{shuffled:?}");
+        assert_eq!(format!("{router:?}"), format!("{expected:?}"));
+    }
+
+    // Failed insertions and removals restore the order.
+    let mut router = build(&routes);
+    router.insert("/c/3", "/c/3".to_string()).unwrap();
+    assert_ne!(router.root, expected.root);
+    assert!(router.insert("/c/1", "/c/1".to_string()).is_err());
+    router.remove("/c/3").unwrap();
+    assert_eq!(router.root, expected.root);
+}
+
+#[test]
+fn limits() {
+    let params = |n: usize| -> String {
+        (0..n)
+            .map(|i| ["/{p", &i.to_string(), "}"].concat())
+            .collect()
+    };
+
+    let mut router = Router::new();
+    assert_eq!(router.insert(params(27), ()), Ok(()));
+    assert_eq!(
+        router.insert(format!("/x{}", params(28)), ()),
+        Err(InsertError::TooManyParams { count: 28, max: 27 })
+    );
+    assert_eq!(
+        router.insert(format!("/y{}/{{*rest}}", params(26)), ()),
+        Ok(())
+    );
+    assert_eq!(router.at(&"/1".repeat(27)).unwrap().params.len(), 27);
+
+    // Stray braces count towards the limit before the route is parsed.
+    assert_eq!(
+        router.insert("{".repeat(1000), ()),
+        Err(InsertError::TooManyParams {
+            count: 1000,
+            max: 27
+        })
+    );
+
+    router.set_max_params(2);
+    assert_eq!(router.insert("/a/{b}/{c}", ()), Ok(()));
+    assert_eq!(
+        router.insert("/a/{b}/{c}/{*d}", ()),
+        Err(InsertError::TooManyParams { count: 3, max: 2 })
+    );
+
+    let long = format!("/{}", "a".repeat(8 * 1024));
+    assert_eq!(
+        router.insert(&long[..8 * 1024], ()),
+        Ok(()),
+        "routes up to 8 KiB are allowed by default"
+    );
+    assert_eq!(
+        router.insert(long.as_str(), ()),
+        Err(InsertError::TemplateTooLong {
+            len: 8 * 1024 + 1,
+            max: 8 * 1024
+        })
+    );
+
+    router.set_max_template_len(4);
+    assert_eq!(
+        router.insert("/abcd", ()),
+        Err(InsertError::TemplateTooLong { len: 5, max: 4 })
+    );
+
+    router.check_invariants().unwrap();
+}
+
+#[test]
+fn invariants() {
+    let segments = ["/", "a", "b", "ab", "{x}", "{y}", "{*z}", "/c", "-"];
+
+    let mut router = Router::new();
+    let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+
+    let mut inserted = Vec::new();
+    for _ in 0..2000 {
+        if rng.below(3) == 0 && !inserted.is_empty() {
+            let route: String = inserted.swap_remove(rng.below(inserted.len()));
+            assert_eq!(router.remove(route.as_str()), Some(route.clone()));
+        } else {
+            let route = (0..1 + rng.below(6))
+                .map(|_| segments[rng.below(segments.len())])
+                .fold("/".to_owned(), |route, segment| route + segment);
+
+            if router.insert(route.as_str(), route.clone()).is_ok() {
+                inserted.push(route);
+            }
+        }
+
+        if let Err(err) = router.check_invariants() {
+            panic!("{err}: {:?}", router.root);
+        }
+    }
+
+    assert_eq!(router.len(), inserted.len());
+}
+
+#[test]
+fn insert_checked() {
+    use CheckedInsertError::*;
+
+    let mut router = Router::new();
+    router.set_max_params(3);
+    router.insert("/users/{id}", ()).unwrap();
+
+    let cases = [
+        ("/posts/{id}", Ok(())),
+        ("/files/{*path}", Ok(())),
+        ("", Err(EmptyRoute)),
+        ("/{}", Err(EmptyParameterName { pos: 1 })),
+        ("/a/{*}", Err(EmptyParameterName { pos: 3 })),
+        ("/{a*b}", Err(InvalidParameterName { pos: 1 })),
+        ("/{**a}", Err(InvalidParameterName { pos: 1 })),
+        ("/{a{b}", Err(InvalidParameterName { pos: 1 })),
+        ("/a/{b", Err(UnclosedBrace { pos: 3 })),
+        ("/a/b}", Err(UnmatchedBrace { pos: 4 })),
+        ("/{a}b", Err(SuffixAfterParameter { pos: 4 })),
+        ("/{a}{b}", Err(SuffixAfterParameter { pos: 4 })),
+        ("/{*a}/b", Err(WildcardNotAtEnd { pos: 1 })),
+        (
+            "/{a}/{b}/{a}",
+            Err(DuplicateParameterName {
+                name: "a".to_owned(),
+                first_pos: 1,
+                second_pos: 9,
+            }),
+        ),
+        (
+            "/{a}/{*a}",
+            Err(DuplicateParameterName {
+                name: "a".to_owned(),
+                first_pos: 1,
+                second_pos: 5,
+            }),
+        ),
+        ("/{a}/{b}/{c}/{d}", Err(TooManyParams { count: 4, max: 3 })),
+        (
+            "/users/{user_id}",
+            Err(Conflict {
+                existing: "/users/{id}".to_owned(),
+            }),
+        ),
+    ];
+
+    for (route, expected) in cases {
+        assert_eq!(router.insert_checked(route, ()), expected, "{route:?}");
+    }
+    assert_eq!(router.len(), 3);
+
+    router.set_strict(true);
+    assert_eq!(
+        router.insert_checked("/a b/{", ()),
+        Err(IllegalCharacter { ch: ' ', pos: 2 })
+    );
+
+    router.set_max_template_len(4);
+    assert_eq!(
+        router.insert_checked("/{a}{", ()),
+        Err(TemplateTooLong { len: 5, max: 4 })
+    );
+}
@@ -0,0 +1,90 @@
+use matchit::Router;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+#[test]
+fn exactly_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let mut router = Router::new();
+    for route in ["/users/{id}", "/posts/{id}"] {
+        let calls = calls.clone();
+        router
+            .insert_lazy(route, move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                thread::yield_now();
+                route.to_owned()
+            })
+            .unwrap();
+    }
+
+    let router = Arc::new(router);
+    let barrier = Arc::new(Barrier::new(16));
+    let threads = (0..16)
+        .map(|i| {
+            let router = router.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                let path = format!("/users/{i}");
+                let matched = router.at_lazy(&path).unwrap();
+                assert_eq!(matched.value, "/users/{id}");
+                assert_eq!(matched.params[0].value, i.to_string().as_bytes());
+                matched.value as *const String as usize
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let values = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap())
+        .collect::<Vec<_>>();
+
+    // Every thread observed the same value, built by a single call.
+    assert!(values.windows(2).all(|pair| pair[0] == pair[1]));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Routes that were never matched are not built.
+    let initialized = router.initialized().collect::<Vec<_>>();
+    assert_eq!(initialized.len(), 1);
+    assert_eq!(initialized[0].0, "/users/{id}");
+}
+
+#[test]
+fn semantics() {
+    let mut router = Router::new();
+    router.insert_lazy("/a", || 1).unwrap();
+    router.insert_lazy("/b", || 2).unwrap();
+    router.insert_lazy("/c", || 3).unwrap();
+
+    // `at` and `at_mut` do not build the value.
+    assert!(!router.at("/a").unwrap().value.is_initialized());
+    assert_eq!(router.at_mut("/a").unwrap().value.get_mut(), None);
+    assert_eq!(router.initialized().count(), 0);
+
+    *router.at_mut("/a").unwrap().value.force_mut() += 10;
+    assert_eq!(*router.at_lazy("/a").unwrap().value, 11);
+    assert_eq!(*router.at_lazy("/b").unwrap().value, 2);
+    assert!(router.at_lazy("/d").is_err());
+
+    let initialized = router.initialized().collect::<Vec<_>>();
+    assert_eq!(initialized, [("/a".to_owned(), &11), ("/b".to_owned(), &2)]);
+
+    // Removing returns the wrapper, which only holds a value if it was built.
+    assert_eq!(router.remove("/b").unwrap().into_inner(), Some(2));
+    assert_eq!(router.remove("/c").unwrap().into_inner(), None);
+    assert_eq!(router.len(), 1);
+}
+
+#[test]
+fn panic_during_init() {
+    let mut router = Router::new();
+    router.insert_lazy("/", || -> u32 { panic!() }).unwrap();
+
+    let router = std::panic::AssertUnwindSafe(router);
+    assert!(std::panic::catch_unwind(|| router.at_lazy("/").map(|m| *m.value)).is_err());
+    assert!(std::panic::catch_unwind(|| router.at_lazy("/").map(|m| *m.value)).is_err());
+    assert!(!router.at("/").unwrap().value.is_initialized());
+}
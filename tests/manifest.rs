@@ -0,0 +1,97 @@
+#![cfg(feature = "manifest")]
+
+use matchit::{ManifestParam, ParamKind, Router};
+use serde_json::json;
+
+#[test]
+fn manifest() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "getUser").unwrap();
+    router.insert("/", "index").unwrap();
+    router
+        .insert("/users/{user_id}/posts/{post_id}", "getPost")
+        .unwrap();
+    router.insert("/static/{*path}", "getStatic").unwrap();
+
+    let manifest = router.manifest_with(|operation| json!({ "operationId": operation }));
+    assert_eq!(
+        serde_json::to_string_pretty(&manifest).unwrap(),
+        r#"{
+  "routes": [
+    {
+      "template": "/",
+      "params": [],
+      "metadata": {
+        "operationId": "index"
+      }
+    },
+    {
+      "template": "/static/{*path}",
+      "params": [
+        {
+          "name": "path",
+          "kind": "catch_all"
+        }
+      ],
+      "metadata": {
+        "operationId": "getStatic"
+      }
+    },
+    {
+      "template": "/users/{id}",
+      "params": [
+        {
+          "name": "id",
+          "kind": "normal"
+        }
+      ],
+      "metadata": {
+        "operationId": "getUser"
+      }
+    },
+    {
+      "template": "/users/{user_id}/posts/{post_id}",
+      "params": [
+        {
+          "name": "user_id",
+          "kind": "normal"
+        },
+        {
+          "name": "post_id",
+          "kind": "normal"
+        }
+      ],
+      "metadata": {
+        "operationId": "getPost"
+      }
+    }
+  ]
+}"#
+    );
+
+    // Metadata is omitted without an extractor.
+    let manifest = router.manifest();
+    assert_eq!(manifest.routes.len(), 4);
+    assert!(manifest.routes.iter().all(|route| route.metadata.is_none()));
+    assert_eq!(
+        serde_json::to_value(&manifest.routes[1]).unwrap(),
+        json!({
+            "template": "/static/{*path}",
+            "params": [{ "name": "path", "kind": "catch_all" }],
+        })
+    );
+
+    assert_eq!(
+        manifest.routes[3].params,
+        vec![
+            ManifestParam {
+                name: "user_id".to_owned(),
+                kind: ParamKind::Normal,
+            },
+            ManifestParam {
+                name: "post_id".to_owned(),
+                kind: ParamKind::Normal,
+            },
+        ]
+    );
+}
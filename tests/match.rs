@@ -1,4 +1,9 @@
-use matchit::{MatchError, Param, Router};
+mod common;
+
+use common::Rng;
+use matchit::{InsertError, MatchError, Param, Pattern, Router, TimeoutOrMatchError};
+
+use std::time::Duration;
 
 // https://github.com/ibraheemdev/matchit/issues/22
 #[test]
@@ -86,6 +91,12 @@ impl MatchTest {
             match router.at(path) {
                 Ok(x) => {
                     assert_eq!(x.value, route);
+                    assert_eq!(router.value_at(path), Some(x.value));
+                    assert!(router.contains_path(path));
+
+                    // Matching the route on its own must be equivalent.
+                    let pattern = Pattern::new(route).unwrap();
+                    assert_eq!(pattern.match_path(path), Some(x.params.clone()), "{route}");
 
                     assert_eq!(
                         params
@@ -103,7 +114,11 @@ impl MatchTest {
                     assert!(router.at(path).unwrap().value.contains('Z'));
                     router.at_mut(path).unwrap().value.pop();
                 }
-                Err(_) => params.unwrap_err(),
+                Err(_) => {
+                    params.unwrap_err();
+                    assert_eq!(router.value_at(path), None);
+                    assert!(!router.contains_path(path));
+                }
             }
         }
     }
@@ -1006,3 +1021,1155 @@ fn wildcard() {
     }
     .run();
 }
+
+#[test]
+fn at_default() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "user").unwrap();
+
+    let (value, params) = router.at_default("/users/1", || unreachable!());
+    assert_eq!(value, "user");
+    assert_eq!(
+        params,
+        vec![Param {
+            key: b"id",
+            value: b"1"
+        }]
+    );
+
+    let mut called = 0;
+    let (value, params) = router.at_default("/posts/1", || {
+        called += 1;
+        "default"
+    });
+    assert_eq!(value, "default");
+    assert!(params.is_empty());
+    assert_eq!(called, 1);
+}
+
+#[test]
+fn pattern() {
+    let pattern = Pattern::new("/src/{*filepath}").unwrap();
+    assert_eq!(pattern.as_str(), "/src/{*filepath}");
+    assert_eq!(
+        pattern.match_path("/src/some/file.png"),
+        Some(vec![Param {
+            key: b"filepath",
+            value: b"some/file.png"
+        }])
+    );
+    assert_eq!(pattern.match_path("/src/"), None);
+    assert_eq!(pattern.match_path("/src"), None);
+
+    let pattern = Pattern::new("/users/{id}/").unwrap();
+    assert!(pattern.match_path("/users/1/").is_some());
+    assert_eq!(pattern.match_path("/users/1"), None);
+    assert_eq!(pattern.match_path("/users/1/x"), None);
+
+    assert_eq!(
+        Pattern::new("/{*x}/y").unwrap_err(),
+        InsertError::InvalidCatchAll
+    );
+}
+
+#[test]
+fn at_with_extra_params() {
+    let mut router = Router::new();
+    router.insert("/{tenant}/users/{id}", "user").unwrap();
+
+    let tenant = String::from("acme");
+    let extra = [
+        Param {
+            key: b"auth",
+            value: b"admin",
+        },
+        Param {
+            key: b"tenant",
+            value: tenant.as_bytes(),
+        },
+    ];
+
+    let matched = router
+        .at_with_extra_params("/other/users/1", extra)
+        .unwrap();
+    assert_eq!(*matched.value, "user");
+    assert_eq!(
+        matched.params,
+        vec![
+            Param {
+                key: b"tenant",
+                value: b"other"
+            },
+            Param {
+                key: b"id",
+                value: b"1"
+            },
+            Param {
+                key: b"auth",
+                value: b"admin"
+            },
+            Param {
+                key: b"tenant",
+                value: b"acme"
+            },
+        ]
+    );
+
+    // Route parameters take precedence.
+    let tenant = matched.params.iter().find(|p| p.key == b"tenant").unwrap();
+    assert_eq!(tenant.value, b"other");
+
+    assert_eq!(
+        router.at_with_extra_params("/users", extra).unwrap_err(),
+        MatchError::NotFound
+    );
+}
+
+#[test]
+fn set_enabled() {
+    let mut router = Router::new();
+    router.insert("/path/foo", "foo").unwrap();
+    router.insert("/path/{*rest}", "wildcard").unwrap();
+    router.insert("/{object}/{id}", "object").unwrap();
+    router.insert("/secret/{id}", "secret").unwrap();
+
+    assert_eq!(router.at("/path/foo").map(|m| *m.value), Ok("foo"));
+    assert_eq!(router.at("/secret/1").map(|m| *m.value), Ok("secret"));
+
+    // Disabled routes fall through to less specific routes.
+    assert!(router.set_enabled("/path/foo", false));
+    assert_eq!(router.at("/path/foo").map(|m| *m.value), Ok("wildcard"));
+    assert!(router.set_enabled("/secret/{id}", false));
+    assert_eq!(router.at("/secret/1").map(|m| *m.value), Ok("object"));
+    assert!(router.set_enabled("/path/{*rest}", false));
+    assert_eq!(router.at("/path/foo").map(|m| *m.value), Ok("object"));
+    assert!(router.set_enabled("/{object}/{id}", false));
+    assert_eq!(router.at("/path/foo").unwrap_err(), MatchError::NotFound);
+    assert!(!router.contains_path("/secret/1"));
+
+    // Disabled routes remain registered.
+    assert_eq!(router.len(), 4);
+    assert_eq!(router.routes().count(), 4);
+    assert!(router.insert("/path/foo", "conflict").is_err());
+
+    // Re-enabling restores precedence.
+    assert!(router.set_enabled("/path/foo", true));
+    assert!(router.set_enabled("/path/{*rest}", true));
+    assert!(router.set_enabled("/secret/{id}", true));
+    assert!(router.set_enabled("/{object}/{id}", true));
+    assert_eq!(router.at("/path/foo").map(|m| *m.value), Ok("foo"));
+    assert_eq!(router.at("/path/baz").map(|m| *m.value), Ok("wildcard"));
+    assert_eq!(router.at("/secret/1").map(|m| *m.value), Ok("secret"));
+
+    // Parameter names must match exactly, and the route must exist.
+    assert!(!router.set_enabled("/secret/{name}", false));
+    assert!(!router.set_enabled("/path", false));
+    assert!(!router.set_enabled("/path/{*path}", false));
+
+    // Removing a disabled route does not affect a route inserted in its place.
+    assert!(router.set_enabled("/path/foo", false));
+    assert_eq!(router.remove("/path/foo"), Some("foo"));
+    router.insert("/path/foo", "new").unwrap();
+    assert_eq!(router.at("/path/foo").map(|m| *m.value), Ok("new"));
+}
+
+#[test]
+fn encoded_slash() {
+    MatchTest {
+        routes: vec!["/files/{path}", "/files/{path}/raw", "/static/a/b"],
+        matches: vec![
+            (
+                "/files/subdir%2Ffile.txt",
+                "/files/{path}",
+                p! { "path" => "subdir%2Ffile.txt" },
+            ),
+            (
+                "/files/a%2Fb/raw",
+                "/files/{path}/raw",
+                p! { "path" => "a%2Fb" },
+            ),
+            ("/files/subdir/file.txt", "", Err(())),
+            ("/static/a%2Fb", "", Err(())),
+        ],
+    }
+    .run();
+}
+
+#[test]
+fn at_bytes() {
+    let mut router = Router::new();
+    router.insert("/files/{name}", "file").unwrap();
+    router.insert("/static/{*path}", "static").unwrap();
+    router.insert("/caf\u{e9}", "cafe").unwrap();
+
+    let matched = router.at_bytes(b"/files/\xff\xfe").unwrap();
+    assert_eq!(*matched.value, "file");
+    assert_eq!(matched.params[0].value, b"\xff\xfe");
+
+    let matched = router.at_bytes(b"/static/\xc3/\xa9").unwrap();
+    assert_eq!(*matched.value, "static");
+    assert_eq!(matched.params[0].value, b"\xc3/\xa9");
+
+    // Static segments are compared byte by byte.
+    assert_eq!(
+        router.at_bytes(b"/caf\xc3\xa9").map(|m| *m.value),
+        Ok("cafe")
+    );
+    assert_eq!(
+        router.at_bytes(b"/caf\xc3").unwrap_err(),
+        MatchError::NotFound
+    );
+    assert_eq!(
+        router.at_bytes(b"/files/a/\xff").unwrap_err(),
+        MatchError::NotFound
+    );
+}
+
+#[test]
+fn at_cb() {
+    let mut router = Router::new();
+    router.insert("/{object}/{id}", "object").unwrap();
+    router.insert("/secret/{id}/path", "secret").unwrap();
+    router.insert("/static/{*path}", "static").unwrap();
+
+    let mut params = Vec::new();
+    let value = router
+        .at_cb("/secret/978", |k, v| params.push((k, v)), |value| *value)
+        .unwrap();
+
+    // Parameters captured before backtracking are discarded.
+    assert_eq!(value, "object");
+    assert_eq!(params, vec![("object", "secret"), ("id", "978")]);
+
+    let mut params = Vec::new();
+    let value = router
+        .at_cb(
+            "/static/caf\u{e9}/a",
+            |k, v| params.push((k, v)),
+            |value| *value,
+        )
+        .unwrap();
+    assert_eq!(value, "static");
+    assert_eq!(params, vec![("path", "caf\u{e9}/a")]);
+
+    let mut called = false;
+    let result = router.at_cb("/a/b/c", |_, _| called = true, |_| ());
+    assert_eq!(result, Err(MatchError::NotFound));
+    assert!(!called);
+
+    // Routes with the maximum number of parameters fit in the buffer.
+    let (mut route, mut path) = (String::new(), String::new());
+    for c in 'a'..='y' {
+        route.extend(['/', '{', c, '}']);
+        path.extend(['/', c]);
+    }
+    route.push_str("/{*rest}");
+    path.push_str("/x/y");
+    router.insert(route, "max").unwrap();
+
+    let mut params = Vec::new();
+    let value = router
+        .at_cb(&path, |k, v| params.push((k, v)), |value| *value)
+        .unwrap();
+    assert_eq!(value, "max");
+    assert_eq!(params.len(), 26);
+    assert_eq!(params[0], ("a", "a"));
+    assert_eq!(params[25], ("rest", "x/y"));
+}
+
+#[test]
+fn at_or_ancestor() {
+    let mut router = Router::new();
+    router.insert("/", "root").unwrap();
+    router.insert("/docs", "docs").unwrap();
+    router.insert("/docs/{page}", "page").unwrap();
+    router.insert("/docs/guide", "guide").unwrap();
+    router.insert("/static/{*path}", "static").unwrap();
+
+    let cases = [
+        ("/docs/guide", "guide", vec![], ""),
+        (
+            "/docs/guide/install/step-3",
+            "guide",
+            vec![],
+            "/install/step-3",
+        ),
+        ("/docs/api/x", "page", vec!["api"], "/x"),
+        ("/docs/", "docs", vec![], "/"),
+        ("/blog/post", "root", vec![], "blog/post"),
+        ("/static/a/b", "static", vec!["a/b"], ""),
+    ];
+
+    for (path, value, params, remaining) in cases {
+        let matched = router.at_or_ancestor(path).unwrap();
+        assert_eq!(*matched.value, value, "{path}");
+        assert_eq!(
+            matched.params.iter().map(|p| p.value).collect::<Vec<_>>(),
+            params.iter().map(|p| p.as_bytes()).collect::<Vec<_>>(),
+            "{path}"
+        );
+        assert_eq!(matched.remaining, remaining, "{path}");
+    }
+
+    router.remove("/").unwrap();
+    assert_eq!(
+        router.at_or_ancestor("/blog/post").unwrap_err(),
+        MatchError::NotFound
+    );
+}
+
+#[test]
+fn at_or_ancestor_without_catch_all() {
+    let mut router = Router::new();
+    router.insert("/{*fallback}", "fallback").unwrap();
+    router.insert("/docs", "docs").unwrap();
+    router.insert("/docs/{page}/{*rest}", "rest").unwrap();
+
+    let matched = router.at_or_ancestor("/docs/guide/install").unwrap();
+    assert_eq!(*matched.value, "rest");
+    assert_eq!(matched.remaining, "");
+
+    let matched = router
+        .at_or_ancestor_without_catch_all("/docs/guide/install")
+        .unwrap();
+    assert_eq!(*matched.value, "docs");
+    assert!(matched.params.is_empty());
+    assert_eq!(matched.remaining, "/guide/install");
+
+    assert_eq!(
+        router
+            .at_or_ancestor_without_catch_all("/blog")
+            .unwrap_err(),
+        MatchError::NotFound
+    );
+}
+
+#[test]
+fn at_with_version() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "unversioned").unwrap();
+    router.insert_versioned(1, "/users/{id}", "v1").unwrap();
+    router.insert_versioned(1, "/users", "v1 list").unwrap();
+    router.insert_versioned(2, "/users/{name}", "v2").unwrap();
+    router
+        .insert_versioned(5, "/users/{id}/posts", "v5")
+        .unwrap();
+
+    assert_eq!(
+        router.insert_versioned(2, "/users/{id}", "v2"),
+        Err(InsertError::Conflict {
+            with: "/users/{name}".into()
+        })
+    );
+
+    let matched = router.at_with_version(2, "/users/1").unwrap();
+    assert_eq!(*matched.value, "v2");
+    assert_eq!(matched.params[0].key, b"name");
+
+    assert_eq!(*router.at_with_version(1, "/users/1").unwrap().value, "v1");
+    assert_eq!(*router.at_with_version(4, "/users/1").unwrap().value, "v2");
+    assert_eq!(
+        *router.at_with_version(9, "/users").unwrap().value,
+        "v1 list"
+    );
+    assert_eq!(
+        *router
+            .at_with_version(u32::MAX, "/users/1/posts")
+            .unwrap()
+            .value,
+        "v5"
+    );
+
+    assert_eq!(
+        router.at_with_version(4, "/users/1/posts").unwrap_err(),
+        MatchError::NotFound
+    );
+    assert_eq!(
+        router.at_with_version(0, "/users/1").unwrap_err(),
+        MatchError::NotFound
+    );
+
+    // Versioned routes are kept separately.
+    assert_eq!(*router.at("/users/1").unwrap().value, "unversioned");
+    assert_eq!(router.at("/users").unwrap_err(), MatchError::NotFound);
+}
+
+#[test]
+fn compile_static_map() {
+    let routes = [
+        "/",
+        "/users",
+        "/users/{id}",
+        "/users/new",
+        "/static/{*path}",
+        "/static/index.html",
+        "/disabled",
+    ];
+
+    let mut router = Router::new();
+    for route in routes {
+        router.insert(route, route).unwrap();
+    }
+    router.set_enabled("/disabled", false);
+
+    let statics = router.compile_static_map();
+    let mut keys = statics.keys().copied().collect::<Vec<_>>();
+    keys.sort_unstable();
+    assert_eq!(keys, ["/", "/static/index.html", "/users", "/users/new"]);
+
+    // Every static route matches itself in the tree.
+    for (path, value) in statics {
+        assert_eq!(router.at(path).unwrap().value, value);
+    }
+
+    assert!(Router::<()>::new().compile_static_map().is_empty());
+}
+
+#[test]
+fn at_timeout() {
+    let mut router = Router::new();
+    router.insert("/{object}/{id}", "object").unwrap();
+    router.insert("/secret/{id}/path", "secret").unwrap();
+
+    // Matching `/secret/978` tries `/secret/{id}/path` first and has to backtrack.
+    let budget = Duration::from_secs(60);
+    assert_eq!(
+        *router.at_timeout("/secret/978", budget).unwrap().value,
+        "object"
+    );
+    assert_eq!(
+        router
+            .at_timeout("/secret/978", Duration::ZERO)
+            .unwrap_err(),
+        TimeoutOrMatchError::Timeout
+    );
+
+    // Paths that do not backtrack are matched regardless of the budget.
+    let matched = router.at_timeout("/secret/1/path", Duration::ZERO).unwrap();
+    assert_eq!(*matched.value, "secret");
+    assert_eq!(matched.params[0].value, b"1");
+    assert_eq!(
+        router.at_timeout("/", Duration::ZERO).unwrap_err(),
+        TimeoutOrMatchError::Match(MatchError::NotFound)
+    );
+    assert!(router.at_timeout("/secret/978", Duration::MAX).is_ok());
+}
+
+#[test]
+fn insert_versioned_group() {
+    let mut router = Router::new();
+    router
+        .insert_versioned_group(1, "/api", [("/users", "v1 list"), ("/users/{id}", "v1")])
+        .unwrap();
+    router
+        .insert_versioned_group(2, "/api", vec![("/users/{id}".to_owned(), "v2")])
+        .unwrap();
+
+    assert_eq!(
+        *router.at_with_version(1, "/api/users/1").unwrap().value,
+        "v1"
+    );
+    assert_eq!(
+        *router.at_with_version(2, "/api/users/1").unwrap().value,
+        "v2"
+    );
+    assert_eq!(
+        *router.at_with_version(2, "/api/users").unwrap().value,
+        "v1 list"
+    );
+    assert!(router.at_with_version(1, "/users").is_err());
+
+    // A failed group is rolled back entirely.
+    let generation = router.generation();
+    assert_eq!(
+        router.insert_versioned_group(
+            2,
+            "/api",
+            [("/posts", "v2 posts"), ("/users/{name}", "v2 conflict")]
+        ),
+        Err(InsertError::Conflict {
+            with: "/api/users/{id}".into()
+        })
+    );
+    assert!(router.at_with_version(2, "/api/posts").is_err());
+    assert_eq!(router.generation(), generation);
+
+    // Including groups for new versions, and routes that fail validation.
+    assert_eq!(
+        router.insert_versioned_group(3, "/api", [("/posts", "v3 posts"), ("/{}", "v3")]),
+        Err(InsertError::InvalidParam)
+    );
+    assert_eq!(
+        router.insert_versioned_group(4, "/api", [("/{}", "v4")]),
+        Err(InsertError::InvalidParam)
+    );
+    assert!(router.at_with_version(3, "/api/posts").is_err());
+    assert_eq!(
+        *router.at_with_version(4, "/api/users/1").unwrap().value,
+        "v2"
+    );
+}
+
+#[test]
+fn param_index() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "user").unwrap();
+    router.insert("/posts/{user}/{id}", "post").unwrap();
+    router.insert("/files/{id}/{*path}", "file").unwrap();
+    router.insert("/static", "static").unwrap();
+
+    let user_id = router.param_index("/users/{id}", "id").unwrap();
+    let post_id = router.param_index("/posts/{user}/{id}", "id").unwrap();
+    let post_user = router.param_index("/posts/{user}/{id}", "user").unwrap();
+    let file_path = router.param_index("/files/{id}/{*path}", "path").unwrap();
+    assert_eq!(user_id.index(), 0);
+    assert_eq!(post_id.index(), 1);
+    assert_eq!(post_user.index(), 0);
+    assert_eq!(file_path.index(), 1);
+
+    let matched = router.at("/users/1").unwrap();
+    assert_eq!(user_id.get(&matched.params), Some(&b"1"[..]));
+
+    let matched = router.at("/posts/alice/2").unwrap();
+    assert_eq!(post_id.get(&matched.params), Some(&b"2"[..]));
+    assert_eq!(post_user.get(&matched.params), Some(&b"alice"[..]));
+
+    let matched = router.at("/files/3/a/b").unwrap();
+    assert_eq!(file_path.get(&matched.params), Some(&b"a/b"[..]));
+
+    // Indices from other routes return whatever is at that position.
+    let matched = router.at("/users/1").unwrap();
+    assert_eq!(post_user.get(&matched.params), Some(&b"1"[..]));
+    assert_eq!(post_id.get(&matched.params), None);
+
+    assert_eq!(router.param_index("/users/{id}", "user"), None);
+    assert_eq!(router.param_index("/users/{user}", "user"), None);
+    assert_eq!(router.param_index("/files/{id}/{*rest}", "rest"), None);
+    assert_eq!(router.param_index("/static", "id"), None);
+    assert_eq!(router.param_index("/missing/{id}", "id"), None);
+    assert_eq!(router.param_index("/posts/{user}", "user"), None);
+}
+
+#[test]
+fn at_pattern() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", ()).unwrap();
+    router.insert("/users/{id}/posts", ()).unwrap();
+    router.insert("/users/new", ()).unwrap();
+    router.insert("/us", ()).unwrap();
+    router.insert("/{*rest}", ()).unwrap();
+
+    assert_eq!(router.at_pattern("/users/1"), Some("/users/{id}"));
+    assert_eq!(
+        router.at_pattern("/users/1/posts"),
+        Some("/users/{id}/posts")
+    );
+    assert_eq!(router.at_pattern("/users/new"), Some("/users/new"));
+    assert_eq!(router.at_pattern("/us"), Some("/us"));
+    assert_eq!(router.at_pattern("/u"), Some("/{*rest}"));
+
+    // Routes are preserved when their nodes are split by later insertions.
+    router.insert("/u", ()).unwrap();
+    assert_eq!(router.at_pattern("/u"), Some("/u"));
+    assert_eq!(router.at_pattern("/us"), Some("/us"));
+
+    router.remove("/us").unwrap();
+    assert_eq!(router.at_pattern("/us"), Some("/{*rest}"));
+}
+
+#[test]
+fn redact() {
+    use matchit::Redacted;
+
+    let mut router = Router::new();
+    for route in [
+        "/",
+        "/users/{id}",
+        "/users/{id}/posts/{post}",
+        "/users/me/settings",
+        "/files/v-{version}/download",
+        "/static/{*path}",
+    ] {
+        router.insert(route, ()).unwrap();
+    }
+
+    let redact = |path: &str| router.redact(path).to_string();
+
+    assert_eq!(router.redact("/users/1"), Redacted::Matched("/users/{id}"));
+    assert_eq!(redact("/static/a/b"), "/static/{*path}");
+
+    assert_eq!(redact("/users/1/posts"), "/users/{id}/posts");
+    assert_eq!(redact("/users/1/posts/2/3"), "/users/{id}/posts/{post}/*");
+    assert_eq!(redact("/users/me/posts/x/y"), "/users/{id}/posts/{post}/*");
+    assert_eq!(redact("/users/me/settings/x"), "/users/me/settings/*");
+    assert_eq!(redact("/users/me/x"), "/users/me/*");
+    assert_eq!(redact("/users/1/"), "/users/{id}/");
+    assert_eq!(redact("/users//x"), "/users//*");
+    assert_eq!(redact("/files/v-2/x"), "/files/v-{version}/*");
+    assert_eq!(redact("/files/v-/x"), "/files/*/*");
+    assert_eq!(redact("/static/"), "/static/");
+    assert_eq!(redact("/secret"), "/*");
+    assert_eq!(redact("secret"), "*");
+    assert_eq!(redact(""), "");
+    assert_eq!(redact("/a/b/c/d/e/f/g/h/i"), "/**");
+    assert_eq!(redact("/a/b/c/d/e/f/g/h"), "/*/*/*/*/*/*/*/*");
+
+    // Unmatched segments are never included in the output.
+    let secrets = [
+        "SECRET",
+        "SECRET%2F..",
+        "S3CR3T-{id}",
+        "{*SECRET}",
+        "users-SECRET",
+        "v-SECRET/../SECRET",
+        "SÉCRET",
+        "me SECRET",
+    ];
+
+    let longest = router.routes().map(|(route, _)| route.len()).max().unwrap();
+    for secret in secrets {
+        for path in [
+            format!("/{secret}"),
+            format!("/users/{secret}/{secret}"),
+            format!("/users/1/posts/2/{secret}"),
+            format!("/users/me/{secret}"),
+            format!("/files/{secret}/download"),
+            format!("/files/v-1/{secret}/{secret}"),
+            format!("/static{secret}"),
+            format!("/{}", [secret; 100].join("/")),
+            format!("{secret}/users/1"),
+        ] {
+            let redacted = redact(&path);
+            assert!(!redacted.contains("SECRET"), "{path} -> {redacted}");
+            assert!(!redacted.contains("S3CR3T"), "{path} -> {redacted}");
+            assert!(!redacted.contains("SÉCRET"), "{path} -> {redacted}");
+            assert!(redacted.len() <= longest + 2 * Redacted::MAX_PLACEHOLDERS);
+        }
+    }
+}
+
+#[test]
+fn version_ranges() {
+    let mut router = Router::new();
+    router
+        .insert_version_range("users/{id}", "users v2-4", 2..=4)
+        .unwrap();
+    router
+        .insert_version_range("/users/{id}", "users v7", 7..=7)
+        .unwrap();
+    router
+        .insert_version_range("/users/{id}", "users v5-6", 5..=6)
+        .unwrap();
+    router.insert_version_range("/", "root", 0..=1).unwrap();
+    router.insert("/v3/users/me", "me").unwrap();
+
+    assert_eq!(
+        router.insert_version_range("/users/{id}", "overlap", 6..=8),
+        Err(InsertError::VersionConflict {
+            route: "/users/{id}".into(),
+            existing: (5, 6),
+            inserted: (6, 8),
+        })
+    );
+    assert_eq!(
+        router.insert_version_range("/users/{name}", "renamed", 9..=9),
+        Err(InsertError::Conflict {
+            with: "/users/{id}".into()
+        })
+    );
+    for route in ["/docs/{version}", "/files/{*version}"] {
+        assert_eq!(
+            router.insert_version_range(route, "reserved", 1..=1),
+            Err(InsertError::ReservedParam {
+                name: "version".into()
+            })
+        );
+    }
+    Router::new()
+        .insert_version_range("/docs/{versions}", "docs", 1..=1)
+        .unwrap();
+
+    for (path, value, params) in [
+        (
+            "/v2/users/1",
+            "users v2-4",
+            vec![("version", "2"), ("id", "1")],
+        ),
+        (
+            "/v4/users/1",
+            "users v2-4",
+            vec![("version", "4"), ("id", "1")],
+        ),
+        (
+            "/v5/users/1",
+            "users v5-6",
+            vec![("version", "5"), ("id", "1")],
+        ),
+        (
+            "/v7/users/1",
+            "users v7",
+            vec![("version", "7"), ("id", "1")],
+        ),
+        ("/v0/", "root", vec![("version", "0")]),
+        // Other routes are preferred.
+        ("/v3/users/me", "me", vec![]),
+    ] {
+        let matched = router.at(path).unwrap();
+        assert_eq!(*matched.value, value, "{path}");
+
+        let params = params
+            .iter()
+            .map(|(key, value)| (key.as_bytes(), value.as_bytes()))
+            .collect::<Vec<_>>();
+        let actual = matched
+            .params
+            .iter()
+            .map(|param| (param.key, param.value))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, params, "{path}");
+    }
+
+    for path in [
+        "/v1/users/1",
+        "/v8/users/1",
+        "/v02/users/1",
+        "/v/users/1",
+        "/vx/users/1",
+        "/v99999999999/users/1",
+        "/V3/users/1",
+        "/v3",
+        "/users/1",
+    ] {
+        assert_eq!(router.at(path).unwrap_err(), MatchError::NotFound, "{path}");
+    }
+
+    let ranges = router
+        .version_ranges()
+        .map(|(route, versions, value)| (route, versions, *value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ranges,
+        [
+            ("/".to_owned(), 0..=1, "root"),
+            ("/users/{id}".to_owned(), 2..=4, "users v2-4"),
+            ("/users/{id}".to_owned(), 5..=6, "users v5-6"),
+            ("/users/{id}".to_owned(), 7..=7, "users v7"),
+        ]
+    );
+
+    router.set_version_prefix("version-");
+    assert_eq!(
+        *router.at("/version-3/users/1").unwrap().value,
+        "users v2-4"
+    );
+    assert!(router.at("/v3/users/1").is_err());
+    router.set_version_prefix("v");
+
+    // Ranges are removed individually, and must match exactly.
+    let generation = router.generation();
+    assert_eq!(router.remove_version_range("/users/{id}", 2..=3), None);
+    assert_eq!(router.remove_version_range("/users/{name}", 2..=4), None);
+    assert_eq!(
+        router.remove_version_range("users/{id}", 2..=4),
+        Some("users v2-4")
+    );
+    assert_eq!(router.generation(), generation + 1);
+    assert!(router.at("/v2/users/1").is_err());
+    assert_eq!(*router.at("/v5/users/1").unwrap().value, "users v5-6");
+
+    assert_eq!(
+        router.at_and_remove_if("/v5/users/1", |_| true),
+        Some("users v5-6")
+    );
+    assert_eq!(
+        router.remove_version_range("/users/{id}", 7..=7),
+        Some("users v7")
+    );
+    assert_eq!(router.remove_version_range("/", 0..=1), Some("root"));
+    assert_eq!(router.version_ranges().count(), 0);
+    assert!(router.at("/v7/users/1").is_err());
+
+    // The route can be inserted again with different parameter names.
+    router
+        .insert_version_range("/users/{name}", "renamed", 9..=9)
+        .unwrap();
+    assert_eq!(*router.at("/v9/users/1").unwrap().value, "renamed");
+}
+
+#[test]
+fn trailing_slash_behavior() {
+    use matchit::TrailingSlashBehavior;
+
+    let router = |behavior| {
+        let mut router = Router::new().with_trailing_slash_behavior(behavior);
+        router.insert("/", "root").unwrap();
+        router.insert("/users", "users").unwrap();
+        router.insert("/users/{id}/", "user").unwrap();
+        router.insert("/static/{*path}", "static").unwrap();
+        router.insert("/dir/", "dir").unwrap();
+        router
+    };
+
+    let at = |router: &Router<&'static str>, path| {
+        router.at(path).map(|matched| {
+            let params = matched
+                .params
+                .iter()
+                .map(|param| std::str::from_utf8(param.value).unwrap())
+                .collect::<Vec<_>>();
+            (*matched.value, params)
+        })
+    };
+
+    // Strict matching is the default.
+    let strict = router(TrailingSlashBehavior::Strict);
+    assert_eq!(at(&strict, "/users"), Ok(("users", vec![])));
+    assert_eq!(at(&strict, "/users/"), Err(MatchError::NotFound));
+    assert_eq!(at(&strict, "/users/1"), Err(MatchError::NotFound));
+    assert_eq!(at(&strict, "/static/a/"), Ok(("static", vec!["a/"])));
+
+    let redirect = router(TrailingSlashBehavior::Redirect);
+    assert_eq!(
+        at(&redirect, "/users/"),
+        Err(MatchError::ExtraTrailingSlash)
+    );
+    assert_eq!(
+        at(&redirect, "/users/1"),
+        Err(MatchError::MissingTrailingSlash)
+    );
+    assert_eq!(at(&redirect, "/dir"), Err(MatchError::MissingTrailingSlash));
+    assert_eq!(at(&redirect, "/users/1/"), Ok(("user", vec!["1"])));
+    assert_eq!(at(&redirect, "/posts/"), Err(MatchError::NotFound));
+    assert_eq!(at(&redirect, "/"), Ok(("root", vec![])));
+
+    let accept = router(TrailingSlashBehavior::Accept);
+    assert_eq!(at(&accept, "/users"), Ok(("users", vec![])));
+    assert_eq!(at(&accept, "/users/"), Ok(("users", vec![])));
+    assert_eq!(at(&accept, "/users/1"), Ok(("user", vec!["1"])));
+    assert_eq!(at(&accept, "/users/1/"), Ok(("user", vec!["1"])));
+    assert_eq!(at(&accept, "/dir"), Ok(("dir", vec![])));
+    assert_eq!(at(&accept, "/static/a/"), Ok(("static", vec!["a/"])));
+    assert_eq!(at(&accept, "/static"), Err(MatchError::NotFound));
+    assert_eq!(at(&accept, "/posts/"), Err(MatchError::NotFound));
+
+    let reject = router(TrailingSlashBehavior::Reject);
+    assert_eq!(at(&reject, "/static/a"), Ok(("static", vec!["a"])));
+    assert_eq!(at(&reject, "/static/a/"), Err(MatchError::NotFound));
+    assert_eq!(at(&reject, "/users/1/"), Ok(("user", vec!["1"])));
+    assert_eq!(at(&reject, "/dir/"), Ok(("dir", vec![])));
+    assert_eq!(at(&reject, "/users/"), Err(MatchError::NotFound));
+    assert_eq!(at(&reject, "/"), Ok(("root", vec![])));
+}
+
+#[test]
+fn trailing_slash_behavior_lookups() {
+    use matchit::TrailingSlashBehavior;
+
+    let mut accept = Router::new().with_trailing_slash_behavior(TrailingSlashBehavior::Accept);
+    accept.insert("/users/{id}", 1).unwrap();
+    accept.insert_version_range("/docs", 2, 1..=2).unwrap();
+
+    assert_eq!(accept.value_at("/users/1/"), Some(&1));
+    assert!(accept.contains_path("/users/1/"));
+    assert_eq!(accept.at_pattern("/users/1/"), Some("/users/{id}"));
+    assert_eq!(
+        accept.at_validated("/users/1/").unwrap().params[0].value,
+        b"1"
+    );
+    assert_eq!(accept.value_at("/v2/docs/"), Some(&2));
+    assert_eq!(accept.at_pattern("/v2/docs"), Some("/docs"));
+
+    let mut id = None;
+    let value = accept.at_cb("/users/1/", |_, value| id = Some(value), |value| *value);
+    assert_eq!((value, id), (Ok(1), Some("1")));
+
+    *accept.at_mut("/users/1/").unwrap().value += 10;
+    *accept.at_mut("/v1/docs/").unwrap().value += 10;
+    assert_eq!(*accept.at("/users/1").unwrap().value, 11);
+    assert_eq!(*accept.at("/v1/docs").unwrap().value, 12);
+
+    assert_eq!(accept.at_and_remove_if("/users/1/", |_| true), Some(11));
+    assert!(!accept.contains_path("/users/1"));
+
+    let mut reject = Router::new().with_trailing_slash_behavior(TrailingSlashBehavior::Reject);
+    reject.insert("/static/{*path}", 1).unwrap();
+
+    assert_eq!(reject.value_at("/static/a/"), None);
+    assert!(!reject.contains_path("/static/a/"));
+    assert_eq!(reject.at_pattern("/static/a/"), None);
+    assert!(reject.at_mut("/static/a/").is_err());
+    assert!(reject.at_cb("/static/a/", |_, _| {}, |_| ()).is_err());
+    assert_eq!(reject.at_and_remove_if("/static/a/", |_| true), None);
+    assert_eq!(reject.value_at("/static/a"), Some(&1));
+
+    let mut redirect = Router::new().with_trailing_slash_behavior(TrailingSlashBehavior::Redirect);
+    redirect.insert("/users", 1).unwrap();
+    redirect.insert("/docs/", 2).unwrap();
+
+    assert_eq!(redirect.value_at("/users/"), None);
+    let err = redirect.at_mut("/users/").unwrap_err();
+    assert_eq!(err, MatchError::ExtraTrailingSlash);
+    assert_eq!(err.redirect_target("/users/").as_deref(), Some("/users"));
+
+    let err = redirect.at_validated("/docs").unwrap_err();
+    assert_eq!(err, MatchError::MissingTrailingSlash);
+    assert_eq!(err.redirect_target("/docs").as_deref(), Some("/docs/"));
+    assert_eq!(MatchError::NotFound.redirect_target("/docs"), None);
+}
+
+#[test]
+fn url_for_round_trip() {
+    let patterns = [
+        "/files/{*path}",
+        "/{*path}",
+        "/users/{id}",
+        "/users/{id}/",
+        "/users/{id}/files/{*path}",
+        "/v-{version}/{page}",
+        "/a/b",
+    ]
+    .map(|route| Pattern::new(route).unwrap());
+
+    for (pattern, path, expected) in [
+        (0, "/files/", None),
+        (0, "/files/a", Some("a")),
+        (0, "/files/a/", Some("a/")),
+        (0, "/files//", Some("/")),
+        (1, "/", None),
+        (1, "//", Some("/")),
+    ] {
+        let params = patterns[pattern].match_path(path);
+        let value = params.as_ref().map(|params| params[0].value);
+        assert_eq!(value, expected.map(str::as_bytes), "{path}");
+
+        if let Some(params) = params {
+            assert_eq!(patterns[pattern].url_for(&params).unwrap(), path);
+        }
+    }
+
+    // Every path that matches round-trips exactly.
+    let mut rng = Rng::new(0x9e37_79b9_7f4a_7c15);
+
+    let mut matched = 0;
+    for _ in 0..20_000 {
+        let segments = ["/", "a", "b", "files", "users", "v-", "-", "1"];
+        let len = rng.below(8);
+        let path = (0..len)
+            .map(|_| segments[rng.below(segments.len())])
+            .collect::<String>();
+
+        for pattern in &patterns {
+            if let Some(params) = pattern.match_path(&path) {
+                matched += 1;
+                assert_eq!(
+                    pattern.url_for(&params).as_deref(),
+                    Some(path.as_str()),
+                    "{} {path}",
+                    pattern.as_str()
+                );
+            }
+        }
+    }
+    assert!(matched > 1000);
+
+    // Parameters that would not round-trip are rejected.
+    let param = |key: &'static str, value: &'static str| Param {
+        key: key.as_bytes(),
+        value: value.as_bytes(),
+    };
+
+    assert_eq!(
+        patterns[2].url_for(&[param("id", "1")]).unwrap(),
+        "/users/1"
+    );
+    assert_eq!(patterns[2].url_for(&[param("id", "a/b")]), None);
+    assert_eq!(patterns[2].url_for(&[param("id", "")]), None);
+    assert_eq!(patterns[2].url_for(&[param("name", "1")]), None);
+    assert_eq!(patterns[0].url_for(&[param("path", "")]), None);
+    assert_eq!(
+        patterns[4]
+            .url_for(&[param("path", "a/b/"), param("id", "1"), param("x", "y")])
+            .unwrap(),
+        "/users/1/files/a/b/"
+    );
+}
+
+#[test]
+fn is_route_reachable() {
+    let mut router = Router::new();
+    for route in [
+        "/{*path}",
+        "/users/{id}",
+        "/users/new",
+        "/users/{id}/posts/{post}",
+        "/files/{*rest}",
+        "/disabled/{id}",
+    ] {
+        router.insert(route, ()).unwrap();
+    }
+    router.set_enabled("/disabled/{id}", false);
+
+    for route in [
+        "/{*path}",
+        "/users/{id}",
+        "/users/{name}",
+        "/users/new",
+        "/users/{id}/posts/{post}",
+        "/files/{*rest}",
+    ] {
+        assert!(router.is_route_reachable(route), "{route}");
+    }
+
+    for route in ["/disabled/{id}", "/users", "/posts/{id}", "/users/{*rest}"] {
+        assert!(!router.is_route_reachable(route), "{route}");
+    }
+
+    let mut router = Router::new();
+    router.insert("/api/{*rest}", ()).unwrap();
+    router
+        .insert_catch_all_with_exclusions("/{*spa}", (), &["/"])
+        .unwrap();
+    assert!(router.is_route_reachable("/api/{*rest}"));
+    assert!(!router.is_route_reachable("/{*spa}"));
+
+    // Static routes may contain control characters outside of strict mode.
+    let mut router = Router::new();
+    router.insert("/users/{id}", ()).unwrap();
+    router.insert("/users/\u{1}", ()).unwrap();
+    router.insert("/users/\u{7f}", ()).unwrap();
+    assert!(router.is_route_reachable("/users/{id}"));
+    assert!(router.is_route_reachable("/users/\u{1}"));
+}
+
+#[test]
+fn insert_with_validator() {
+    let numeric = |value: &str| value.bytes().all(|b| b.is_ascii_digit());
+
+    let mut router = Router::new();
+    router
+        .insert_with_validator("/api/users/{id}/posts/{post}", 1, numeric)
+        .unwrap();
+    router
+        .insert_with_validator("/api/files/{*path}", 2, |path| !path.contains(".."))
+        .unwrap();
+    router.insert("/api/users/{id}", 3).unwrap();
+
+    for (path, value, valid) in [
+        ("/api/users/1/posts/2", 1, true),
+        ("/api/users/1/posts/x", 1, false),
+        ("/api/users/x/posts/2", 1, false),
+        ("/api/files/a/b.txt", 2, true),
+        ("/api/files/a/../b.txt", 2, false),
+        ("/api/users/x", 3, true),
+    ] {
+        let matched = router.at_validated(path).unwrap();
+        assert_eq!((*matched.value, matched.valid), (value, valid), "{path}");
+    }
+
+    // Other methods ignore the validator.
+    assert_eq!(*router.at("/api/users/x/posts/2").unwrap().value, 1);
+    assert_eq!(
+        router.at_validated("/api/posts").unwrap_err(),
+        MatchError::NotFound
+    );
+
+    // Validators are kept when a prefix is stripped and shared between clones.
+    router.strip_prefix_from_all("/api").unwrap();
+    assert!(!router.at_validated("/users/x/posts/2").unwrap().valid);
+    let clone = router.clone();
+    assert!(!clone.at_validated("/users/x/posts/2").unwrap().valid);
+    assert_eq!(clone.root, router.root);
+
+    // Validators are removed along with the route.
+    assert_eq!(router.remove("/users/{id}/posts/{post}"), Some(1));
+    router.insert("/users/{id}/posts/{post}", 4).unwrap();
+    assert!(router.at_validated("/users/x/posts/2").unwrap().valid);
+    assert_ne!(clone.root, router.root);
+}
+
+#[test]
+fn at_encoded_slash() {
+    let mut router = Router::new();
+    router.insert("/{bucket}/{*key}", "object").unwrap();
+    router.insert("/{bucket}", "bucket").unwrap();
+    router.insert("/files/{a}/{b}", "file").unwrap();
+
+    let params = |router: &Router<&'static str>, path| {
+        let decoded = router.at_encoded_slash(path).unwrap();
+        let raw = decoded.matched.params.iter().map(|param| param.value);
+        let raw = raw.map(|value| std::str::from_utf8(value).unwrap().to_owned());
+        let values = decoded.values.iter().map(|value| value.to_string());
+        (*decoded.matched.value, raw.zip(values).collect::<Vec<_>>())
+    };
+
+    let pairs = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|&(raw, value)| (raw.to_owned(), value.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(params(&router, "/b"), ("bucket", pairs(&[("b", "b")])));
+    assert_eq!(
+        params(&router, "/b%2Fk"),
+        ("object", pairs(&[("b", "b"), ("k", "k")]))
+    );
+    assert_eq!(
+        params(&router, "/b%2fk%2F"),
+        ("object", pairs(&[("b", "b"), ("k%2F", "k/")]))
+    );
+    assert_eq!(
+        params(&router, "/b/x%2Fy"),
+        ("object", pairs(&[("b", "b"), ("x%2Fy", "x/y")]))
+    );
+    assert_eq!(
+        params(&router, "/files%2Fa%2Fb"),
+        ("file", pairs(&[("a", "a"), ("b", "b")]))
+    );
+    assert_eq!(
+        params(&router, "/b%2F..%2Fc"),
+        ("object", pairs(&[("b", "b"), ("..%2Fc", "../c")]))
+    );
+    assert_eq!(
+        params(&router, "/bucket/dir%2Ffile"),
+        (
+            "object",
+            pairs(&[("bucket", "bucket"), ("dir%2Ffile", "dir/file")])
+        )
+    );
+
+    // Other encoded sequences are not decoded.
+    assert_eq!(
+        params(&router, "/b%2Ek"),
+        ("bucket", pairs(&[("b%2Ek", "b%2Ek")]))
+    );
+    assert_eq!(router.at("/b%2Fk").unwrap().params[0].value, b"b%2Fk");
+    assert_eq!(
+        router.at_encoded_slash("%2F").unwrap_err(),
+        MatchError::NotFound
+    );
+
+    // Paths that do not match with encoded slashes as separators are matched as given.
+    let mut router = Router::new();
+    router.insert("/bucket/{key}", "object").unwrap();
+    assert_eq!(
+        params(&router, "/bucket/dir%2Ffile"),
+        ("object", pairs(&[("dir%2Ffile", "dir/file")]))
+    );
+
+    // Matching with encoded slashes as separators is preferred.
+    router.insert("/bucket/dir/{file}", "file").unwrap();
+
+    assert_eq!(
+        params(&router, "/bucket/dir%2Ffile"),
+        ("file", pairs(&[("file", "file")]))
+    );
+    assert_eq!(
+        params(&router, "/bucket/a%2Fb%2Fc"),
+        ("object", pairs(&[("a%2Fb%2Fc", "a/b/c")]))
+    );
+    assert_eq!(
+        router.at_encoded_slash("/bucket/a%2Fb/c").unwrap_err(),
+        MatchError::NotFound
+    );
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "nginx")]
+
+use matchit::nginx::{self, ConvertError, Location, LocationKind, Modifier};
+use matchit::Router;
+
+// A reference implementation of nginx's matching rules for exact and prefix locations.
+fn nginx_match(locations: &[Location], path: &str) -> Option<usize> {
+    let exact = locations
+        .iter()
+        .position(|l| l.modifier == Modifier::Exact && l.path == path);
+
+    exact.or_else(|| {
+        locations
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| l.modifier != Modifier::Exact && path.starts_with(&l.path))
+            .max_by_key(|(_, l)| l.path.len())
+            .map(|(i, _)| i)
+    })
+}
+
+#[test]
+fn differential() {
+    use Modifier::*;
+
+    let locations = [
+        Location::new(Exact, "/"),
+        Location::new(Prefix, "/"),
+        Location::new(Prefix, "/api"),
+        Location::new(Prefix, "/api/"),
+        Location::new(Exact, "/api/health"),
+        Location::new(PreferPrefix, "/static/"),
+        Location::new(Prefix, "/apix/v1"),
+        Location::new(Prefix, "/a"),
+        Location::new(Prefix, "/ab/"),
+        Location::new(Exact, "/ab/"),
+        Location::new(Prefix, "/docs/v1/"),
+        Location::new(Exact, "/docs"),
+        Location::new(PreferPrefix, "/docs/v1/guide"),
+    ];
+
+    let mut router = Router::new();
+    for (route, kind) in nginx::convert(&locations).unwrap() {
+        router.insert(route, kind).unwrap();
+    }
+
+    let segments = [
+        "", "a", "ab", "abc", "api", "apix", "apiary", "health", "static", "docs", "v1", "guide",
+        "guides", "x",
+    ];
+
+    let mut paths = vec![String::new()];
+    for _ in 0..3 {
+        let mut next = Vec::new();
+        for path in &paths {
+            for segment in segments {
+                next.push(format!("{path}/{segment}"));
+            }
+        }
+        paths.extend(next);
+    }
+
+    for path in paths.iter().filter(|path| !path.is_empty()) {
+        let expected = nginx_match(&locations, path);
+        let actual = router.at(path).ok().map(|matched| matched.value.index());
+        assert_eq!(actual, expected, "{path}");
+
+        if let Ok(matched) = router.at(path) {
+            let exact = matches!(matched.value, LocationKind::Exact(_));
+            assert_eq!(exact, locations[matched.value.index()].modifier == Exact);
+        }
+    }
+}
+
+#[test]
+fn errors() {
+    use Modifier::*;
+
+    let convert = |locations: &[(Modifier, &str)]| {
+        let locations = locations
+            .iter()
+            .map(|&(modifier, path)| Location::new(modifier, path))
+            .collect::<Vec<_>>();
+        nginx::convert(&locations)
+    };
+
+    assert_eq!(
+        convert(&[(Prefix, "/"), (Regex, r"\.php$")]),
+        Err(ConvertError::Regex {
+            location: r"~ \.php$".to_owned()
+        })
+    );
+    assert_eq!(
+        convert(&[(RegexCaseInsensitive, r"\.(gif|jpg)$")]),
+        Err(ConvertError::Regex {
+            location: r"~* \.(gif|jpg)$".to_owned()
+        })
+    );
+    assert_eq!(
+        convert(&[(Prefix, "@fallback")]),
+        Err(ConvertError::UnsupportedPath {
+            location: "@fallback".to_owned()
+        })
+    );
+    assert_eq!(
+        convert(&[(Exact, "/{id}")]),
+        Err(ConvertError::UnsupportedPath {
+            location: "= /{id}".to_owned()
+        })
+    );
+    assert_eq!(
+        convert(&[(Prefix, "/api"), (PreferPrefix, "/api")]),
+        Err(ConvertError::Duplicate {
+            location: "^~ /api".to_owned()
+        })
+    );
+
+    assert_eq!(
+        convert(&[(Prefix, "/api"), (Exact, "/api")]),
+        Ok(vec![
+            ("/api{*rest}".to_owned(), LocationKind::Prefix(0)),
+            ("/api".to_owned(), LocationKind::Exact(1)),
+        ])
+    );
+}
@@ -0,0 +1,72 @@
+use matchit::{RouteObserver, Router};
+
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl RouteObserver<i32> for Recorder {
+    fn on_insert(&mut self, route: &str, value: &i32) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("insert {route} {value}"));
+    }
+
+    fn on_remove(&mut self, route: &str, value: &i32) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("remove {route} {value}"));
+    }
+}
+
+impl Recorder {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+#[test]
+fn observer() {
+    let recorder = Recorder::default();
+
+    let mut router = Router::new();
+    router.insert("/before", 0).unwrap();
+    router.set_observer(Box::new(recorder.clone()));
+
+    router.insert("/users/{id}", 1).unwrap();
+    router.insert("/users/{id}/posts", 2).unwrap();
+    router.insert("/static/{*path}", 3).unwrap();
+    assert_eq!(
+        recorder.take(),
+        [
+            "insert /users/{id} 1",
+            "insert /users/{id}/posts 2",
+            "insert /static/{*path} 3"
+        ]
+    );
+
+    // Failed mutations are not observed.
+    assert!(router.insert("/users/{name}", 4).is_err());
+    assert!(router.insert("/users/{id", 4).is_err());
+    assert_eq!(router.remove("/users/{name}"), None);
+    assert_eq!(router.remove("/missing"), None);
+    assert!(recorder.take().is_empty());
+
+    assert_eq!(router.remove("/users/{id}"), Some(1));
+    assert_eq!(router.remove("/before"), Some(0));
+    assert_eq!(
+        recorder.take(),
+        ["remove /users/{id} 1", "remove /before 0"]
+    );
+
+    // Clones do not share the observer.
+    let mut clone = router.clone();
+    clone.insert("/clone", 5).unwrap();
+    assert!(recorder.take().is_empty());
+
+    assert!(router.take_observer().is_some());
+    router.insert("/after", 6).unwrap();
+    assert!(recorder.take().is_empty());
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "openapi")]
+
+use matchit::Router;
+use serde_json::json;
+
+#[test]
+fn openapi_paths() {
+    let mut router = Router::new();
+    router.insert("/users", ()).unwrap();
+    router.insert("/users/{id}/posts/{post}", ()).unwrap();
+    router.insert("/static/{*path}", ()).unwrap();
+
+    assert_eq!(
+        router.to_openapi_paths(),
+        json!({
+            "/users": {},
+            "/users/{id}/posts/{post}": {
+                "parameters": [
+                    {
+                        "name": "id",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    },
+                    {
+                        "name": "post",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    },
+                ],
+            },
+            "/static/{path}": {
+                "parameters": [
+                    {
+                        "name": "path",
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    },
+                ],
+            },
+        })
+    );
+
+    assert_eq!(Router::<()>::new().to_openapi_paths(), json!({}));
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "profile")]
+
+use matchit::{MatchError, Router};
+
+#[test]
+fn count_match_attempts() {
+    let mut router = Router::new();
+    router.insert("/{object}/{id}", "object with id").unwrap();
+    router.insert("/secret/{id}/path", "secret").unwrap();
+
+    let (result, direct) = router.count_match_attempts("/secret/978/path");
+    assert_eq!(*result.unwrap().value, "secret");
+
+    // Matching this path requires backtracking to the `{object}` wildcard.
+    let (result, backtracked) = router.count_match_attempts("/secret/978");
+    assert_eq!(*result.unwrap().value, "object with id");
+    assert!(backtracked > direct, "{backtracked} <= {direct}");
+
+    let (result, attempts) = router.count_match_attempts("");
+    assert_eq!(result.unwrap_err(), MatchError::NotFound);
+    assert_eq!(attempts, 1);
+}
@@ -226,6 +226,21 @@ fn trailing_slash() {
     .run();
 }
 
+#[test]
+fn catchall_with_siblings() {
+    RemoveTest {
+        routes: vec!["/{*rest}", "/a", "/b/{id}"],
+        ops: vec![
+            (Remove, "/{*rest}", Some("/{*rest}")),
+            (Remove, "/b/{*rest}", None),
+            (Insert, "/b/{id}/{*rest}", None),
+            (Remove, "/b/{id}/{*rest}", Some("/b/{id}/{*rest}")),
+        ],
+        remaining: vec!["/a", "/b/1"],
+    }
+    .run();
+}
+
 #[test]
 fn remove_root() {
     RemoveTest {
@@ -235,3 +250,85 @@ fn remove_root() {
     }
     .run();
 }
+
+#[test]
+fn peek_remove() {
+    let mut router = Router::new();
+    router.insert("/users/{user_id}", "user").unwrap();
+    router
+        .insert("/users/{user_id}/posts/{post}", "post")
+        .unwrap();
+    router.insert("/static/{*path}", "static").unwrap();
+    router.insert("/", "root").unwrap();
+
+    // Shape-equal routes with different parameter names are found, but not removed.
+    assert_eq!(
+        router.peek_remove("/users/{id}").as_deref(),
+        Some("/users/{user_id}")
+    );
+    assert_eq!(router.remove("/users/{id}"), None);
+    assert_eq!(
+        router.peek_remove("/users/{a}/posts/{b}").as_deref(),
+        Some("/users/{user_id}/posts/{post}")
+    );
+    assert_eq!(router.remove("/users/{a}/posts/{b}"), None);
+
+    assert_eq!(
+        router.peek_remove("/static/{*path}").as_deref(),
+        Some("/static/{*path}")
+    );
+    assert_eq!(router.peek_remove("/").as_deref(), Some("/"));
+    assert_eq!(router.peek_remove("/users"), None);
+    assert_eq!(router.peek_remove("/users/{id}/posts"), None);
+    assert_eq!(router.peek_remove("/static/{*file}"), None);
+    assert_eq!(router.peek_remove("/users/{id"), None);
+
+    // Peeking does not modify the router.
+    assert_eq!(router.len(), 4);
+    assert_eq!(router.remove("/users/{user_id}"), Some("user"));
+    assert_eq!(router.peek_remove("/users/{id}"), None);
+    assert_eq!(router.remove("/users/{user_id}/posts/{post}"), Some("post"));
+}
+
+#[test]
+fn at_and_remove_if() {
+    let mut router = Router::new();
+    router.insert("/tokens/{token}", "any").unwrap();
+    router.insert("/tokens/once", "once").unwrap();
+    router.insert("/files/{*path}", "files").unwrap();
+
+    let generation = router.generation();
+    assert_eq!(router.at_and_remove_if("/tokens/once", |_| false), None);
+    assert_eq!(router.at_and_remove_if("/missing", |_| true), None);
+    assert_eq!(router.generation(), generation);
+    assert_eq!(router.len(), 3);
+
+    assert_eq!(
+        router.at_and_remove_if("/tokens/once", |value| *value == "once"),
+        Some("once")
+    );
+    assert_eq!(*router.at("/tokens/once").unwrap().value, "any");
+
+    // The matched route is removed, not the path.
+    assert_eq!(
+        router.at_and_remove_if("/files/a/b", |_| true),
+        Some("files")
+    );
+    assert_eq!(
+        router.at_and_remove_if("/tokens/abc", |_| true),
+        Some("any")
+    );
+    assert!(router.is_empty());
+    router.check_invariants().unwrap();
+}
+
+#[test]
+fn take() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "user".to_owned()).unwrap();
+
+    assert_eq!(router.take("/users/{name}"), None);
+    assert_eq!(router.take("/users/{id}").as_deref(), Some("user"));
+    assert_eq!(router.take("/users/{id}"), None);
+    assert!(router.is_empty());
+}
@@ -0,0 +1,434 @@
+mod common;
+
+use common::Rng;
+use matchit::{Router, TreeVisitor};
+
+use std::collections::BTreeSet;
+
+fn router(routes: &[&'static str]) -> Router<&'static str> {
+    let mut router = Router::new();
+    for route in routes {
+        router.insert(*route, *route).unwrap();
+    }
+    router
+}
+
+#[test]
+fn sorted() {
+    let router = router(&[
+        "/users/{id}",
+        "/",
+        "/users",
+        "/posts/{year}/{slug}",
+        "/posts",
+        "/static/{*path}",
+        "/users/{id}/posts",
+    ]);
+
+    let routes = router.routes().collect::<Vec<_>>();
+    assert_eq!(
+        routes,
+        vec![
+            ("/".to_owned(), &"/"),
+            ("/posts".to_owned(), &"/posts"),
+            ("/posts/{year}/{slug}".to_owned(), &"/posts/{year}/{slug}"),
+            ("/static/{*path}".to_owned(), &"/static/{*path}"),
+            ("/users".to_owned(), &"/users"),
+            ("/users/{id}".to_owned(), &"/users/{id}"),
+            ("/users/{id}/posts".to_owned(), &"/users/{id}/posts"),
+        ]
+    );
+
+    let mut reversed = router.routes().rev().collect::<Vec<_>>();
+    reversed.reverse();
+    assert_eq!(reversed, routes);
+
+    for route in router.routes() {
+        assert_eq!(route.0, *route.1);
+    }
+}
+
+#[test]
+fn exact_size() {
+    let router = router(&["/a", "/b", "/c/{x}", "/d/{*x}", "/e"]);
+
+    let mut routes = router.routes();
+    assert_eq!(routes.len(), 5);
+
+    assert_eq!(routes.next().unwrap().0, "/a");
+    assert_eq!(routes.next_back().unwrap().0, "/e");
+    assert_eq!(routes.len(), 3);
+
+    assert_eq!(routes.nth(1).unwrap().0, "/c/{x}");
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes.clone().last().unwrap().0, "/d/{*x}");
+
+    assert_eq!(routes.nth_back(0).unwrap().0, "/d/{*x}");
+    assert_eq!(routes.len(), 0);
+    assert_eq!(routes.next(), None);
+    assert_eq!(routes.next_back(), None);
+
+    assert_eq!(router.routes().nth(10), None);
+    assert_eq!(
+        router.routes().skip(3).map(|(r, _)| r).collect::<Vec<_>>(),
+        ["/d/{*x}", "/e"]
+    );
+}
+
+#[test]
+fn nth_route() {
+    let router = router(&[
+        "/users/{id}",
+        "/",
+        "/posts/{year}/{slug}",
+        "/static/{*path}",
+    ]);
+
+    for (i, (route, value)) in router.routes().enumerate() {
+        assert_eq!(router.nth_route(i), Some((route.as_str(), value)));
+    }
+
+    assert_eq!(router.nth_route(4), None);
+    assert_eq!(Router::<()>::new().nth_route(0), None);
+}
+
+#[test]
+fn empty() {
+    let router = Router::<()>::new();
+    assert!(router.is_empty());
+    assert_eq!(router.routes().len(), 0);
+    assert_eq!(router.routes().next(), None);
+}
+
+#[test]
+fn counts() {
+    let mut rng = Rng::new(0x2545_f491);
+
+    let segments = ["a", "ab", "b", "{x}", "{y}", "{*z}", ""];
+
+    let mut router = Router::new();
+    let mut expected = BTreeSet::new();
+
+    for _ in 0..2000 {
+        let mut route = String::new();
+        for _ in 0..=rng.below(3) {
+            route.push('/');
+            route.push_str(segments[rng.below(segments.len())]);
+        }
+
+        if rng.below(3) == 0 {
+            if router.remove(route.clone()).is_some() {
+                assert!(expected.remove(&route), "{route}");
+            }
+        } else if router.insert(route.clone(), ()).is_ok() {
+            expected.insert(route);
+        }
+
+        router.check_priorities().unwrap();
+        assert_eq!(router.len(), expected.len());
+        assert_eq!(router.routes().len(), expected.len());
+    }
+
+    let routes = router
+        .routes()
+        .map(|(route, _)| route)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(routes, expected);
+
+    for (i, route) in router.routes().enumerate() {
+        assert_eq!(router.routes().nth(i), Some(route.clone()));
+        assert_eq!(router.routes().rev().nth(router.len() - i - 1), Some(route));
+    }
+}
+
+#[test]
+fn overlapping_wildcards() {
+    // At most one catch-all route.
+    assert!(!router(&[]).has_overlapping_wildcards());
+    assert!(!router(&["/files/{*path}", "/posts/{id}/x", "/{page}"]).has_overlapping_wildcards());
+
+    // Nested catch-all routes.
+    assert!(router(&["/files/{*path}", "/files/public/{*path}"]).has_overlapping_wildcards());
+
+    // A route parameter can match the static prefix of another catch-all route.
+    assert!(router(&["/files/{*path}", "/{dir}/{*path}"]).has_overlapping_wildcards());
+    assert!(router(&["/user_{name}/{*rest}", "/user_x/{*rest}"]).has_overlapping_wildcards());
+
+    // Disjoint catch-all routes.
+    assert!(!router(&["/files/{*path}", "/static/{*path}"]).has_overlapping_wildcards());
+    assert!(
+        !router(&["/files/{*path}", "/static/{*path}", "/a/b/{*c}"]).has_overlapping_wildcards()
+    );
+    assert!(router(&["/files/{*path}", "/{dir}/x/{*path}"]).has_overlapping_wildcards());
+
+    // Route parameters do not match `/`.
+    assert!(!router(&["/{a}/x/{*path}", "/b/c/x/{*path}"]).has_overlapping_wildcards());
+}
+
+#[test]
+fn visit() {
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl TreeVisitor<&'static str> for Recorder {
+        fn visit_static_node(&mut self, prefix: &[u8], depth: usize) {
+            let prefix = std::str::from_utf8(prefix).unwrap();
+            self.0.push(format!("{depth} static {prefix}"));
+        }
+
+        fn visit_dynamic_node(&mut self, prefix: &[u8], depth: usize) {
+            let prefix = std::str::from_utf8(prefix).unwrap();
+            self.0.push(format!("{depth} param {prefix}"));
+        }
+
+        fn visit_wildcard_node(&mut self, prefix: &[u8], depth: usize) {
+            let prefix = std::str::from_utf8(prefix).unwrap();
+            self.0.push(format!("{depth} catch-all {prefix}"));
+        }
+
+        fn visit_value(&mut self, route: &str, value: &&'static str) {
+            assert_eq!(route, *value);
+            self.0.push(format!("value {route}"));
+        }
+    }
+
+    let router = router(&["/users/{id}", "/users/{id}/posts", "/static/{*path}"]);
+
+    let mut recorder = Recorder::default();
+    router.visit(&mut recorder);
+    assert_eq!(
+        recorder.0,
+        [
+            "0 static /",
+            "1 static users/",
+            "2 param {a}",
+            "value /users/{id}",
+            "3 static /posts",
+            "value /users/{id}/posts",
+            "1 static static/",
+            "2 catch-all {*path}",
+            "value /static/{*path}",
+        ]
+    );
+
+    // The default methods do nothing.
+    struct Noop;
+    impl<T> TreeVisitor<T> for Noop {}
+    router.visit(&mut Noop);
+}
+
+#[test]
+fn paths_matching_prefix() {
+    let router = router(&[
+        "/",
+        "/users",
+        "/users/{id}",
+        "/users/{id}/posts",
+        "/users/new",
+        "/user-{name}/profile",
+        "/posts/{post}",
+        "/static/{*path}",
+        "/{page}/edit",
+    ]);
+
+    let matching = |prefix| router.paths_matching_prefix(prefix).collect::<Vec<_>>();
+
+    assert_eq!(matching("").len(), router.len());
+    assert_eq!(matching("/users/4"), ["/users/{id}", "/users/{id}/posts"]);
+    assert_eq!(
+        matching("/users/n"),
+        ["/users/new", "/users/{id}", "/users/{id}/posts"]
+    );
+    assert_eq!(matching("/users/4/p"), ["/users/{id}/posts"]);
+    assert_eq!(matching("/users/4/x"), Vec::<String>::new());
+    assert_eq!(
+        matching("/user"),
+        [
+            "/user-{name}/profile",
+            "/users",
+            "/users/new",
+            "/users/{id}",
+            "/users/{id}/posts",
+            "/{page}/edit"
+        ]
+    );
+    assert_eq!(
+        matching("/user-bob/"),
+        ["/user-{name}/profile", "/{page}/edit"]
+    );
+    assert_eq!(matching("/static/a/b"), ["/static/{*path}"]);
+    assert_eq!(matching("/about/e"), ["/{page}/edit"]);
+    assert_eq!(matching("/posts/1/"), Vec::<String>::new());
+    assert_eq!(matching("posts"), Vec::<String>::new());
+}
+
+#[test]
+fn summary() {
+    let users = router(&[
+        "/users",
+        "/users/{id}",
+        "/users/{id}/posts/{post}",
+        "/users/{id}/files/{*path}",
+        "/usé",
+    ]);
+
+    struct CountNodes(usize);
+
+    impl<T> TreeVisitor<T> for CountNodes {
+        fn visit_static_node(&mut self, _prefix: &[u8], _depth: usize) {
+            self.0 += 1;
+        }
+
+        fn visit_dynamic_node(&mut self, _prefix: &[u8], _depth: usize) {
+            self.0 += 1;
+        }
+
+        fn visit_wildcard_node(&mut self, _prefix: &[u8], _depth: usize) {
+            self.0 += 1;
+        }
+    }
+
+    let mut nodes = CountNodes(0);
+    users.visit(&mut nodes);
+
+    let summary = users.summary();
+    assert_eq!(summary.total_routes, 5);
+    assert_eq!(summary.static_routes, 2);
+    assert_eq!(summary.dynamic_routes, 2);
+    assert_eq!(summary.wildcard_routes, 1);
+    assert_eq!(summary.total_nodes, nodes.0);
+    assert_eq!(summary.common_prefix.as_deref(), Some("/us"));
+    assert!(summary.max_depth >= 4);
+
+    let summary = router(&["/a", "b"]).summary();
+    assert_eq!(summary.common_prefix, None);
+    assert_eq!(summary.total_nodes, 3);
+    assert_eq!(summary.max_depth, 1);
+
+    // The common prefix does not split characters.
+    let summary = router(&["/é", "/è"]).summary();
+    assert_eq!(summary.common_prefix.as_deref(), Some("/"));
+
+    assert_eq!(Router::<()>::new().summary(), Default::default());
+}
+
+#[test]
+fn deep_clone() {
+    let mut original = Router::new();
+    original.insert("/users/{id}", vec![1]).unwrap();
+    original.insert("/static/{*path}", vec![2]).unwrap();
+
+    let mut copy = original.deep_clone();
+    copy.at_mut("/users/1").unwrap().value.push(3);
+    copy.remove("/static/{*path}");
+
+    assert_eq!(*original.at("/users/1").unwrap().value, [1]);
+    assert_eq!(*original.at("/static/a").unwrap().value, [2]);
+    assert_eq!(*copy.at("/users/1").unwrap().value, [1, 3]);
+    assert!(copy.at("/static/a").is_err());
+}
+
+#[test]
+fn format_as_table() {
+    let router = router(&[
+        "/users/{user_id}/posts",
+        "/users/{user_id}/files",
+        "/static/{*path}",
+    ]);
+
+    assert_eq!(
+        router.format_as_table(),
+        "\
+| Route Pattern          | Has Value | Node Type | Depth |
+| ---------------------- | --------- | --------- | ----- |
+| /                      | no        | root      | 0     |
+| /users/                | no        | static    | 1     |
+| /users/{user_id}       | no        | param     | 2     |
+| /users/{user_id}/      | no        | static    | 3     |
+| /users/{user_id}/files | yes       | static    | 4     |
+| /users/{user_id}/posts | yes       | static    | 4     |
+| /static/               | no        | static    | 1     |
+| /static/{*path}        | yes       | catch-all | 2     |
+"
+    );
+
+    assert_eq!(
+        Router::<()>::new().format_as_table(),
+        "\
+| Route Pattern | Has Value | Node Type | Depth |
+| ------------- | --------- | --------- | ----- |
+"
+    );
+}
+
+#[test]
+fn has_dynamic_routes() {
+    let mut router = Router::new();
+    assert!(router.all_routes_static());
+    assert!(!router.has_dynamic_routes());
+
+    router.insert("/users", 1).unwrap();
+    router.insert("/users/{id}", 2).unwrap();
+    router.insert("/files/{*path}", 3).unwrap();
+    assert!(router.has_dynamic_routes());
+
+    // Failed insertions and removals do not change the count.
+    router.insert("/users/{id}", 4).unwrap_err();
+    assert_eq!(router.remove("/users/{user_id}"), None);
+    assert_eq!(router.remove("/users/{id}"), Some(2));
+    assert!(router.has_dynamic_routes());
+    assert!(router.clone().has_dynamic_routes());
+
+    assert_eq!(router.remove("/files/{*path}"), Some(3));
+    assert!(router.all_routes_static());
+
+    // Stripping a prefix may remove the only route parameter.
+    let mut router = Router::new();
+    router.insert("/{tenant}/users", 1).unwrap();
+    router.insert("/{tenant}/posts", 2).unwrap();
+    assert!(router.has_dynamic_routes());
+    router.strip_prefix_from_all("/{tenant}").unwrap();
+    assert!(router.all_routes_static());
+    assert_eq!(*router.at("/users").unwrap().value, 1);
+}
+
+#[test]
+fn count_shared_prefixes() {
+    let users = router(&[
+        "/users",
+        "/users/{id}",
+        "/users/{id}/posts",
+        "/users/new",
+        "/files/{*path}",
+    ]);
+
+    let counts = users.count_shared_prefixes();
+    let expected = [
+        ("/", 5),
+        ("/users", 4),
+        ("/users/", 3),
+        ("/users/{id}", 2),
+        ("/users/{id}/posts", 1),
+        ("/users/new", 1),
+        ("/files/", 1),
+        ("/files/{*path}", 1),
+    ];
+    assert_eq!(counts.len(), expected.len(), "{counts:?}");
+    for (prefix, count) in expected {
+        assert_eq!(counts.get(prefix), Some(&count), "{prefix}");
+    }
+    assert_eq!(counts.values().sum::<usize>(), 18);
+
+    // Removing a route may leave behind nodes that no route passes through.
+    let mut removed = router(&["/a", "/a/{x}/b"]);
+    removed.remove("/a/{x}/b");
+    let counts = removed.count_shared_prefixes();
+    assert_eq!(counts["/a"], 1);
+    assert_eq!(
+        counts.values().filter(|&&count| count == 0).count(),
+        2,
+        "{counts:?}"
+    );
+
+    assert!(Router::<()>::new().count_shared_prefixes().is_empty());
+}
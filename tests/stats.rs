@@ -0,0 +1,43 @@
+#![cfg(feature = "stats")]
+
+use matchit::{MatchError, MatchStats, Router};
+
+#[test]
+fn at_with_stats() {
+    let mut router = Router::new();
+    router.insert("/{object}/{id}", "object with id").unwrap();
+    router.insert("/secret/{id}/path", "secret").unwrap();
+    router.insert("/files/{*path}", "file").unwrap();
+
+    let (result, direct) = router.at_with_stats("/secret/978/path");
+    assert_eq!(*result.unwrap().value, "secret");
+    assert_eq!(direct.backtracks, 0);
+    assert_eq!(direct.param_captures, 1);
+
+    // Matching this path requires backtracking to the `{object}` wildcard.
+    let (result, backtracked) = router.at_with_stats("/secret/978");
+    assert_eq!(*result.unwrap().value, "object with id");
+    assert_eq!(backtracked.backtracks, 1);
+    assert!(backtracked.nodes_visited > direct.nodes_visited);
+    assert_eq!(backtracked.param_captures, 2);
+
+    // Parameters captured by abandoned routes are counted as well.
+    let (result, abandoned) = router.at_with_stats("/secret/978/other");
+    assert_eq!(result.unwrap_err(), MatchError::NotFound);
+    assert_eq!(abandoned.param_captures, 2);
+
+    let (result, stats) = router.at_with_stats("/files/a/b");
+    assert_eq!(result.unwrap().params[0].value, b"a/b");
+    assert_eq!(stats.param_captures, 1);
+
+    let (result, stats) = router.at_with_stats("");
+    assert_eq!(result.unwrap_err(), MatchError::NotFound);
+    assert_eq!(
+        stats,
+        MatchStats {
+            nodes_visited: 1,
+            match_duration_ns: stats.match_duration_ns,
+            ..MatchStats::default()
+        }
+    );
+}
@@ -0,0 +1,140 @@
+use matchit::{InsertError, Router, StripError};
+
+#[test]
+fn strip_prefix() {
+    let mut router = Router::new();
+    router.insert("/api/v1/", "index").unwrap();
+    router.insert("/api/v1/users/{id}", "user").unwrap();
+    router.insert("/api/v1/static/{*path}", "static").unwrap();
+    router.set_enabled("/api/v1/", false);
+
+    assert_eq!(router.strip_prefix_from_all("/api/v1"), Ok(3));
+    assert_eq!(router.generation(), 4);
+    router.check_priorities().unwrap();
+
+    assert_eq!(
+        router.iter_with_generation().collect::<Vec<_>>(),
+        vec![
+            ("/".to_owned(), &"index", 1),
+            ("/static/{*path}".to_owned(), &"static", 3),
+            ("/users/{id}".to_owned(), &"user", 2),
+        ]
+    );
+
+    let matched = router.at("/users/978").unwrap();
+    assert_eq!(*matched.value, "user");
+    assert_eq!(matched.params[0].key, b"id");
+    assert_eq!(matched.params[0].value, b"978");
+    assert_eq!(*router.at("/static/a/b").unwrap().value, "static");
+    assert!(router.at("/api/v1/users/978").is_err());
+
+    // Disabled routes remain disabled.
+    assert!(router.at("/").is_err());
+    assert!(router.set_enabled("/", true));
+    assert_eq!(*router.at("/").unwrap().value, "index");
+}
+
+#[test]
+fn strip_prefix_error() {
+    let mut router = Router::new();
+    router.insert("/api/users", "users").unwrap();
+    router.insert("/api/users/{id}", "user").unwrap();
+    router.insert("/health", "health").unwrap();
+
+    assert_eq!(
+        router.strip_prefix_from_all("/api"),
+        Err(StripError::NotUniversal {
+            route: "/health".to_owned()
+        })
+    );
+
+    router.remove("/health").unwrap();
+
+    // Stripping part of a parameter results in an invalid route.
+    assert_eq!(
+        router.strip_prefix_from_all("/api/users/{"),
+        Err(StripError::NotUniversal {
+            route: "/api/users".to_owned()
+        })
+    );
+    router.remove("/api/users").unwrap();
+    assert_eq!(
+        router.strip_prefix_from_all("/api/users/{"),
+        Err(StripError::MissingLeadingSlash {
+            route: "/api/users/{id}".to_owned()
+        })
+    );
+
+    // Stripped routes must start with a `/`.
+    assert_eq!(
+        router.strip_prefix_from_all("/api/u"),
+        Err(StripError::MissingLeadingSlash {
+            route: "/api/users/{id}".to_owned()
+        })
+    );
+
+    // The router is left unchanged.
+    assert_eq!(
+        router.routes().collect::<Vec<_>>(),
+        vec![("/api/users/{id}".to_owned(), &"user")]
+    );
+    assert_eq!(*router.at("/api/users/1").unwrap().value, "user");
+
+    assert_eq!(Router::<()>::new().strip_prefix_from_all("/api"), Ok(0));
+}
+
+#[test]
+fn strip_prefix_exact() {
+    let mut router = Router::new();
+    router.insert("/api", "index").unwrap();
+    router.insert("/api/users", "users").unwrap();
+
+    assert_eq!(router.strip_prefix_from_all("/api"), Ok(2));
+    assert_eq!(*router.at("/").unwrap().value, "index");
+    assert_eq!(*router.at("/users").unwrap().value, "users");
+    assert_eq!(router.value_at("/api"), None);
+
+    // Both routes would become `/`.
+    let mut router = Router::new();
+    router.insert("/api", "index").unwrap();
+    router.insert("/api/", "slash").unwrap();
+
+    assert_eq!(
+        router.strip_prefix_from_all("/api"),
+        Err(StripError::Insert(InsertError::Conflict {
+            with: "/".to_owned()
+        }))
+    );
+    assert_eq!(*router.at("/api").unwrap().value, "index");
+}
+
+#[test]
+fn strip_prefix_validate() {
+    // Stripped routes are checked against limits set after they were inserted.
+    let mut router = Router::new();
+    router.insert("/{tenant}/users/{id}", "user").unwrap();
+    router.set_max_params(0);
+
+    assert_eq!(
+        router.strip_prefix_from_all("/{tenant}"),
+        Err(StripError::Insert(InsertError::TooManyParams {
+            count: 1,
+            max: 0
+        }))
+    );
+    assert_eq!(*router.at("/acme/users/1").unwrap().value, "user");
+
+    // Stripped routes are checked against the path grammar in strict mode.
+    let mut router = Router::new();
+    router.insert("/a/ b", "space").unwrap();
+    router.set_strict(true);
+
+    assert_eq!(
+        router.strip_prefix_from_all("/a"),
+        Err(StripError::Insert(InsertError::IllegalCharacter {
+            ch: ' ',
+            at: 1
+        }))
+    );
+    assert_eq!(*router.at("/a/ b").unwrap().value, "space");
+}
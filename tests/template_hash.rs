@@ -0,0 +1,47 @@
+use matchit::{template_hash, Router, TEMPLATE_HASH_VERSION};
+
+#[test]
+fn golden() {
+    // Changing any of these values requires incrementing `TEMPLATE_HASH_VERSION`.
+    assert_eq!(TEMPLATE_HASH_VERSION, 1);
+    assert_eq!(template_hash(b""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(template_hash(b"/users/{id}"), 0x1e58_f59e_23b9_1970);
+    assert_eq!(template_hash(b"/users/{user_id}"), 0x0b28_fe14_bcdd_7c5c);
+    assert_eq!(template_hash(b"/static/{*path}"), 0xb11c_313c_ae89_4e6a);
+}
+
+#[test]
+fn matched() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "user").unwrap();
+    router.insert("/users/{id}/posts/{post}", "post").unwrap();
+    router.insert("/static/{*path}", "static").unwrap();
+
+    let user = router.at("/users/1").unwrap().template_hash();
+    assert_eq!(user, 0x1e58_f59e_23b9_1970);
+    assert_eq!(router.at("/users/2").unwrap().template_hash(), user);
+    assert_eq!(router.at_mut("/users/3").unwrap().template_hash(), user);
+
+    assert_eq!(
+        router.at("/users/1/posts/2").unwrap().template_hash(),
+        0x9944_e5d5_b837_d1f7
+    );
+    assert_eq!(
+        router.at("/static/a/b").unwrap().template_hash(),
+        0xb11c_313c_ae89_4e6a
+    );
+
+    // Hashes are preserved when an insertion splits the node holding the value.
+    router.insert("/user", "user").unwrap();
+    assert_eq!(router.at("/users/1").unwrap().template_hash(), user);
+
+    assert_eq!(
+        router.template_hashes().collect::<Vec<_>>(),
+        vec![
+            (0xb11c_313c_ae89_4e6a, "/static/{*path}".to_owned()),
+            (template_hash(b"/user"), "/user".to_owned()),
+            (user, "/users/{id}".to_owned()),
+            (0x9944_e5d5_b837_d1f7, "/users/{id}/posts/{post}".to_owned()),
+        ]
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "test-util")]
+
+use matchit::test_util::FailingRouter;
+use matchit::{InsertError, MatchError, Router, StripError};
+
+// A minimal framework that maps router errors to status codes.
+struct App {
+    router: FailingRouter<Vec<(&'static str, u16)>>,
+}
+
+impl App {
+    fn route(&mut self, route: &str, method: &'static str) -> u16 {
+        if let Ok(matched) = self.router.at_mut(route) {
+            matched.value.push((method, 200));
+            return 200;
+        }
+
+        match self.router.insert(route, vec![(method, 200)]) {
+            Ok(()) => 200,
+            Err(_) => 500,
+        }
+    }
+
+    fn handle(&self, method: &str, path: &str) -> u16 {
+        match self.router.at(path) {
+            Ok(matched) => matched
+                .value
+                .iter()
+                .find(|(m, _)| *m == method)
+                .map_or(405, |(_, status)| *status),
+            Err(MatchError::ExtraTrailingSlash | MatchError::MissingTrailingSlash) => 308,
+            Err(_) => 404,
+        }
+    }
+}
+
+#[test]
+fn status_codes() {
+    let mut app = App {
+        router: FailingRouter::new(Router::new()),
+    };
+
+    assert_eq!(app.route("/users/{id}", "GET"), 200);
+    assert_eq!(app.route("/slow/{*rest}", "GET"), 200);
+
+    assert_eq!(app.handle("GET", "/users/1"), 200);
+    assert_eq!(app.handle("POST", "/users/1"), 405);
+    assert_eq!(app.handle("GET", "/posts/1"), 404);
+
+    app.router
+        .fail_at("/slow/{*rest}", MatchError::NotFound)
+        .unwrap();
+    app.router
+        .fail_at("/users/{id}/", MatchError::ExtraTrailingSlash)
+        .unwrap();
+    assert_eq!(app.handle("GET", "/slow/query"), 404);
+    assert_eq!(app.handle("GET", "/users/1/"), 308);
+    assert_eq!(app.handle("GET", "/users/1"), 200);
+
+    app.router.fail_next_insert(InsertError::Conflict {
+        with: "/posts/{id}".to_owned(),
+    });
+    assert_eq!(app.route("/posts/{id}", "GET"), 500);
+    assert_eq!(app.route("/posts/{id}", "GET"), 200);
+
+    app.router.clear_failures();
+    assert_eq!(app.handle("GET", "/slow/query"), 200);
+}
+
+#[test]
+fn every_error() {
+    let mut router = FailingRouter::new(Router::new());
+
+    let match_errors = [
+        ("/not-found", MatchError::NotFound),
+        ("/extra/", MatchError::ExtraTrailingSlash),
+        ("/missing", MatchError::MissingTrailingSlash),
+    ];
+
+    for (path, error) in match_errors {
+        router.insert(path, ()).unwrap();
+        router.fail_at(path, error).unwrap();
+        assert_eq!(router.at(path).unwrap_err(), error);
+        assert_eq!(router.at_mut(path).unwrap_err(), error);
+    }
+
+    let insert_errors = [
+        InsertError::Conflict {
+            with: "/a".to_owned(),
+        },
+        InsertError::InvalidParamSegment,
+        InsertError::InvalidParam,
+        InsertError::InvalidCatchAll,
+        InsertError::IllegalCharacter { ch: '<', at: 1 },
+        InsertError::VersionConflict {
+            route: "/a".to_owned(),
+            existing: (1, 2),
+            inserted: (2, 3),
+        },
+    ];
+
+    for error in insert_errors.clone() {
+        router.fail_next_insert(error);
+    }
+
+    for error in insert_errors {
+        assert_eq!(router.insert("/a", ()), Err(error));
+    }
+
+    router.fail_insert("/b", InsertError::InvalidParam);
+    assert_eq!(router.insert("/b", ()), Err(InsertError::InvalidParam));
+    assert_eq!(router.insert("/b", ()), Err(InsertError::InvalidParam));
+    assert_eq!(router.insert("/a", ()), Ok(()));
+
+    let strip_errors = [
+        StripError::NotUniversal {
+            route: "/a".to_owned(),
+        },
+        StripError::Insert(InsertError::InvalidParam),
+    ];
+
+    for error in strip_errors.clone() {
+        router.fail_next_strip(error);
+    }
+
+    for error in strip_errors {
+        assert_eq!(router.strip_prefix_from_all("/"), Err(error));
+    }
+
+    // Failed operations do not modify the wrapped router.
+    assert_eq!(router.len(), 4);
+    assert!(router.into_inner().at("/b").is_err());
+}
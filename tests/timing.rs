@@ -0,0 +1,48 @@
+#![cfg(feature = "timing")]
+
+use matchit::{Clock, MatchError, Router, SystemClock};
+
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+// A clock that advances by a fixed step every time it is read.
+struct FakeClock {
+    now: Cell<Instant>,
+    step: Duration,
+    reads: Cell<usize>,
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        let now = self.now.get() + self.step;
+        self.now.set(now);
+        self.reads.set(self.reads.get() + 1);
+        now
+    }
+}
+
+#[test]
+fn at_with_clock() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", "A User").unwrap();
+
+    let clock = FakeClock {
+        now: Cell::new(Instant::now()),
+        step: Duration::from_millis(5),
+        reads: Cell::new(0),
+    };
+
+    let timed = router.at_with_clock("/users/978", &clock).unwrap();
+    assert_eq!(*timed.matched.value, "A User");
+    assert_eq!(timed.matched.params[0].value, b"978");
+    assert_eq!(timed.lookup_duration, Duration::from_millis(5));
+    assert_eq!(clock.reads.get(), 2);
+
+    assert_eq!(
+        router.at_with_clock("/posts/1", &clock).unwrap_err(),
+        MatchError::NotFound
+    );
+
+    let timed = router.at_with_clock("/users/1", &SystemClock).unwrap();
+    assert_eq!(*timed.matched.value, "A User");
+}
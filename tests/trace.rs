@@ -0,0 +1,63 @@
+#![cfg(feature = "trace")]
+
+use matchit::tree::NodeType;
+use matchit::{Router, TraceStep};
+
+#[test]
+fn backtracking() {
+    let mut router = Router::new();
+    router.insert("/{object}/{id}", "object with id").unwrap();
+    router.insert("/secret/{id}/path", "secret").unwrap();
+
+    let trace = router.trace_match("/secret/978");
+    assert!(trace.matched);
+    assert_eq!(trace.path, "/secret/978");
+
+    // The static `secret/` child is searched first, and the search backtracks to
+    // the skipped `{object}` wildcard once it fails.
+    assert!(trace.steps.contains(&TraceStep::Skip {
+        path: b"/secret/978"
+    }));
+    assert!(trace.steps.contains(&TraceStep::Backtrack {
+        path: b"/secret/978"
+    }));
+    assert_eq!(
+        trace.steps.last(),
+        Some(&TraceStep::Param { value: b"978" })
+    );
+
+    let rendered = trace.to_string();
+    assert!(rendered.starts_with("matching `/secret/978`"));
+    assert!(rendered.contains("backtrack to wildcard at `/secret/978`"));
+    assert!(rendered.ends_with("found"));
+}
+
+#[test]
+fn not_found() {
+    let mut router = Router::new();
+    router.insert("/home", "Welcome!").unwrap();
+    router.insert("/files/{*path}", "files").unwrap();
+
+    let trace = router.trace_match("/blo");
+    assert!(!trace.matched);
+    assert_eq!(
+        trace.steps,
+        vec![TraceStep::Compare {
+            node_type: NodeType::Root,
+            prefix: b"/".to_vec(),
+            path: b"/blo",
+            matched: true,
+        }]
+    );
+    assert!(trace.to_string().ends_with("not found"));
+
+    let trace = router.trace_match("/files/a/b");
+    assert!(trace.matched);
+    assert_eq!(
+        trace.steps.last(),
+        Some(&TraceStep::CatchAll { value: b"a/b" })
+    );
+
+    // Tracing does not affect the match itself.
+    assert_eq!(router.at("/files/a/b").unwrap().params.len(), 1);
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "trie-bytes")]
+
+use matchit::{ImportError, Router};
+
+#[test]
+fn round_trip() {
+    let routes = [
+        "/",
+        "/users",
+        "/users/{id}",
+        "/users/{id}/posts/{post}",
+        "/users/{user_id}/files/{*path}",
+        "/posts/{id}",
+        "/posts/new",
+        "/static/{*path}",
+        "/{*fallback}",
+    ];
+
+    let mut router = Router::new();
+    for (i, route) in routes.iter().enumerate() {
+        router.insert(*route, (i, route.to_string())).unwrap();
+    }
+    router
+        .insert("/removed/{id}/nested", (0, String::new()))
+        .unwrap();
+    router.remove("/removed/{id}/nested");
+    router.set_enabled("/posts/new", false);
+
+    let bytes = router.export_trie_bytes();
+    let imported = Router::<(usize, String)>::import_trie_bytes(&bytes).unwrap();
+
+    assert_eq!(imported.len(), routes.len());
+    for (route, value) in router.routes() {
+        assert_eq!(imported.peek_remove(route.as_str()), Some(route.clone()));
+        assert!(imported.routes().any(|(r, v)| r == route && v == value));
+    }
+
+    for path in [
+        "/",
+        "/users/1",
+        "/users/1/files/a/b",
+        "/posts/new",
+        "/removed/1",
+    ] {
+        assert_eq!(
+            imported.at(path).map(|m| m.value),
+            router.at(path).map(|m| m.value),
+            "{path}"
+        );
+    }
+
+    // Exporting the imported router produces the same bytes, apart from removed routes.
+    let mut expected = Router::new();
+    for (route, value) in router.routes() {
+        expected.insert(route, value.clone()).unwrap();
+    }
+    expected.set_enabled("/posts/new", false);
+    assert_eq!(imported.export_trie_bytes(), expected.export_trie_bytes());
+
+    let empty = Router::<()>::new().export_trie_bytes();
+    assert!(Router::<()>::import_trie_bytes(&empty).unwrap().is_empty());
+}
+
+#[test]
+fn invalid() {
+    let mut router = Router::new();
+    router.insert("/users/{id}", 1).unwrap();
+    router.insert("/posts", 2).unwrap();
+    let bytes = router.export_trie_bytes();
+
+    let import = |bytes: &[u8]| Router::<u32>::import_trie_bytes(bytes).map(|_| ());
+
+    assert_eq!(import(b""), Err(ImportError::InvalidHeader));
+    assert_eq!(import(b"MTRE\x02"), Err(ImportError::InvalidHeader));
+    for len in 5..bytes.len() {
+        assert_eq!(import(&bytes[..len]), Err(ImportError::UnexpectedEnd));
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        import(&trailing),
+        Err(ImportError::Malformed {
+            offset: bytes.len()
+        })
+    );
+
+    // The type of the root node, after its prefix `/`.
+    let mut node_type = bytes.clone();
+    node_type[10] = 4;
+    assert_eq!(
+        import(&node_type),
+        Err(ImportError::Malformed { offset: 10 })
+    );
+
+    let replace = |from: &[u8], to: &[u8]| {
+        let i = bytes.windows(from.len()).position(|w| w == from).unwrap();
+        [&bytes[..i], to, &bytes[i + from.len()..]].concat()
+    };
+
+    assert_eq!(
+        import(&replace(b"/users/{id}", b"/users/{ab}")),
+        Ok(()),
+        "parameter names are not part of the tree"
+    );
+    assert_eq!(
+        import(&replace(b"/users/{id}", b"/usurs/{id}")),
+        Err(ImportError::Inconsistent {
+            route: "/usurs/{id}".to_owned()
+        })
+    );
+    assert!(matches!(
+        Router::<String>::import_trie_bytes(&bytes),
+        Err(ImportError::Value { .. })
+    ));
+
+    let mut conflict = Router::new();
+    conflict.insert("/{a}", 1).unwrap();
+    conflict.insert("/{*b}", 2).unwrap_err();
+    let mut bytes = conflict.export_trie_bytes();
+    // Turn the parameter into a catch-all with the same route length.
+    let i = bytes.windows(3).position(|w| w == b"{a}").unwrap();
+    bytes[i + 1] = b'*';
+    let error = import(&bytes).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ImportError::Inconsistent { .. } | ImportError::Insert { .. }
+        ),
+        "{error:?}"
+    );
+}